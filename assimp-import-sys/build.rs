@@ -0,0 +1,155 @@
+#[cfg(feature = "bindgen")]
+extern crate bindgen_crate as bindgen;
+#[cfg(feature = "bindgen")]
+extern crate cc;
+
+#[cfg(feature = "bindgen")]
+fn generate() {
+    use std::env;
+    use std::path::PathBuf;
+
+    println!("cargo:rerun-if-changed=wrapper.h");
+    println!("cargo:rerun-if-env-changed=ASSIMP_INCLUDE_DIR");
+    println!("cargo:rustc-link-lib=dylib=assimp");
+
+    let mut builder = bindgen::Builder::default()
+        .header("wrapper.h")
+        .ctypes_prefix("::libc")
+        .allowlist_type("ai.*")
+        .allowlist_function("ai.*")
+        .allowlist_var("AI_.*")
+        // declared by hand in lib.rs as usize
+        .blocklist_item("AI_MAX_NUMBER_OF_.*")
+        .rustified_enum("ai.*")
+        .derive_default(true);
+    if let Ok(dir) = env::var("ASSIMP_INCLUDE_DIR") {
+        builder = builder.clang_arg(format!("-I{}", dir));
+    }
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    builder
+        .generate()
+        .expect("failed to generate assimp bindings")
+        .write_to_file(out_dir.join("bindings.rs"))
+        .expect("failed to write assimp bindings");
+    check_layout(&out_dir);
+}
+
+/// The structs used by the safe wrapper and their fields, see
+/// `check_layout()`.
+#[cfg(feature = "bindgen")]
+const LAYOUT_FIELDS: &[(&str, &[&str])] = &[
+    ("aiAnimMesh", &["mVertices", "mNormals", "mTangents", "mBitangents", "mColors", "mTextureCoords", "mNumVertices"]),
+    ("aiAnimation", &["mName", "mDuration", "mTicksPerSecond", "mNumChannels", "mChannels", "mNumMeshChannels", "mMeshChannels"]),
+    ("aiBone", &["mName", "mNumWeights", "mWeights", "mOffsetMatrix"]),
+    ("aiCamera", &["mName", "mPosition", "mUp", "mLookAt", "mHorizontalFOV", "mClipPlaneNear", "mClipPlaneFar", "mAspect"]),
+    ("aiColor3D", &["r", "g", "b"]),
+    ("aiColor4D", &["r", "g", "b", "a"]),
+    ("aiFace", &["mNumIndices", "mIndices"]),
+    ("aiFile", &["ReadProc", "WriteProc", "TellProc", "FileSizeProc", "SeekProc", "FlushProc", "UserData"]),
+    ("aiFileIO", &["OpenProc", "CloseProc", "UserData"]),
+    ("aiImporterDesc", &["mName", "mAuthor", "mMaintainer", "mComments", "mFlags", "mMinMajor", "mMinMinor", "mMaxMajor", "mMaxMinor", "mFileExtensions"]),
+    ("aiLight", &["mName", "mType", "mPosition", "mDirection", "mUp", "mAttenuationConstant", "mAttenuationLinear", "mAttenuationQuadratic", "mColorDiffuse", "mColorSpecular", "mColorAmbient", "mAngleInnerCone", "mAngleOuterCone", "mSize"]),
+    ("aiMaterial", &["mProperties", "mNumProperties", "mNumAllocated"]),
+    ("aiMaterialProperty", &["mKey", "mSemantic", "mIndex", "mDataLength", "mType", "mData"]),
+    ("aiMatrix4x4", &["a1", "a2", "a3", "a4", "b1", "b2", "b3", "b4", "c1", "c2", "c3", "c4", "d1", "d2", "d3", "d4"]),
+    ("aiMesh", &["mPrimitiveTypes", "mNumVertices", "mNumFaces", "mVertices", "mNormals", "mTangents", "mBitangents", "mColors", "mTextureCoords", "mNumUVComponents", "mFaces", "mNumBones", "mBones", "mMaterialIndex", "mName", "mNumAnimMeshes", "mAnimMeshes"]),
+    ("aiMeshAnim", &["mName", "mNumKeys", "mKeys"]),
+    ("aiMeshKey", &["mTime", "mValue"]),
+    ("aiMetadata", &["mNumProperties", "mKeys", "mValues"]),
+    ("aiMetadataEntry", &["mType", "mData"]),
+    ("aiNode", &["mName", "mTransformation", "mParent", "mNumChildren", "mChildren", "mNumMeshes", "mMeshes", "mMetaData"]),
+    ("aiNodeAnim", &["mNodeName", "mNumPositionKeys", "mPositionKeys", "mNumRotationKeys", "mRotationKeys", "mNumScalingKeys", "mScalingKeys", "mPreState", "mPostState"]),
+    ("aiQuatKey", &["mTime", "mValue"]),
+    ("aiQuaternion", &["w", "x", "y", "z"]),
+    ("aiScene", &["mFlags", "mRootNode", "mNumMeshes", "mMeshes", "mNumMaterials", "mMaterials", "mNumAnimations", "mAnimations", "mNumTextures", "mTextures", "mNumLights", "mLights", "mNumCameras", "mCameras"]),
+    ("aiString", &["length", "data"]),
+    ("aiTexel", &["b", "g", "r", "a"]),
+    ("aiTexture", &["mWidth", "mHeight", "achFormatHint", "pcData"]),
+    ("aiUVTransform", &["mTranslation", "mScaling", "mRotation"]),
+    ("aiVector2D", &["x", "y"]),
+    ("aiVector3D", &["x", "y", "z"]),
+    ("aiVectorKey", &["mTime", "mValue"]),
+    ("aiVertexWeight", &["mVertexId", "mWeight"]),
+];
+
+/// Compiles and runs a C program printing the size and field offsets of
+/// every struct in `LAYOUT_FIELDS` as the C compiler sees them in the
+/// installed headers, and writes `layout_check.rs` asserting the
+/// regenerated bindings agree. A mismatch fails the build.
+///
+/// The program can't run when cross-compiling, the check is skipped then.
+#[cfg(feature = "bindgen")]
+fn check_layout(out_dir: &std::path::Path) {
+    use std::env;
+    use std::fmt::Write;
+    use std::fs;
+    use std::process::Command;
+
+    let check = out_dir.join("layout_check.rs");
+    if env::var("HOST").ok() != env::var("TARGET").ok() {
+        println!("cargo:warning=cross-compiling, skipping the struct layout check against the assimp headers");
+        fs::write(&check, "").expect("failed to write layout_check.rs");
+        return;
+    }
+
+    let mut c = String::from("#include <stddef.h>\n#include <stdio.h>\n#include \"wrapper.h\"\n\nint main(void) {\n");
+    for &(ty, fields) in LAYOUT_FIELDS {
+        writeln!(c, "    printf(\"%zu\\n\", sizeof(struct {}));", ty).unwrap();
+        for field in fields {
+            writeln!(c, "    printf(\"%zu\\n\", offsetof(struct {}, {}));", ty, field).unwrap();
+        }
+    }
+    c.push_str("    return 0;\n}\n");
+    let source = out_dir.join("layout_check.c");
+    fs::write(&source, c).expect("failed to write layout_check.c");
+
+    let mut build = cc::Build::new();
+    build.include(env::var("CARGO_MANIFEST_DIR").unwrap());
+    if let Ok(dir) = env::var("ASSIMP_INCLUDE_DIR") {
+        build.include(dir);
+    }
+    let compiler = build.get_compiler();
+    let exe = out_dir.join("layout_check");
+    let mut cmd = compiler.to_command();
+    cmd.arg(&source);
+    if compiler.is_like_msvc() {
+        cmd.arg(format!("/Fe{}", exe.display()));
+    } else {
+        cmd.arg("-o").arg(&exe);
+    }
+    let status = cmd.status().expect("failed to run the C compiler for the layout check");
+    assert!(status.success(), "failed to compile the struct layout check against the assimp headers");
+    let output = Command::new(&exe).output().expect("failed to run the struct layout check");
+    let values = String::from_utf8(output.stdout).expect("layout check printed invalid UTF-8");
+    let mut values = values.lines().map(|v| v.trim().parse::<usize>().expect("layout check printed a non-number"));
+
+    let mut rust = String::from("// Generated by build.rs from the installed assimp headers.\n");
+    for &(ty, fields) in LAYOUT_FIELDS {
+        writeln!(
+            rust,
+            "const _: () = assert!(::std::mem::size_of::<{0}>() == {1}, \"size of {0} differs from the assimp headers\");",
+            ty,
+            values.next().unwrap()
+        )
+        .unwrap();
+        for field in fields {
+            writeln!(
+                rust,
+                "const _: () = assert!(::std::mem::offset_of!({0}, {1}) == {2}, \"offset of {0}::{1} differs from the assimp headers\");",
+                ty,
+                field,
+                values.next().unwrap()
+            )
+            .unwrap();
+        }
+    }
+    fs::write(&check, rust).expect("failed to write layout_check.rs");
+}
+
+#[cfg(not(feature = "bindgen"))]
+fn generate() {}
+
+fn main() {
+    generate();
+}
@@ -1,11 +1,3 @@
-#![allow(dead_code,
-         non_camel_case_types,
-         non_upper_case_globals,
-         non_snake_case)]
-
-pub const AI_MAX_NUMBER_OF_COLOR_SETS: usize = 0x8;
-pub const AI_MAX_NUMBER_OF_TEXTURECOORDS: usize = 0x8;
-
 // automatically generated by rust-bindgen
 
 pub type __u_char = ::libc::c_uchar;
@@ -753,7 +745,66 @@ impl ::std::default::Default for aiImporterDesc {
         unsafe { ::std::mem::zeroed() }
     }
 }
-pub enum aiFileIO { }
+pub type aiFileWriteProc =
+    ::std::option::Option<unsafe extern "C" fn(arg1: *mut aiFile,
+                                                  arg2: *const ::libc::c_char,
+                                                  arg3: size_t,
+                                                  arg4: size_t)
+                              -> size_t>;
+pub type aiFileReadProc =
+    ::std::option::Option<unsafe extern "C" fn(arg1: *mut aiFile,
+                                                  arg2: *mut ::libc::c_char,
+                                                  arg3: size_t,
+                                                  arg4: size_t)
+                              -> size_t>;
+pub type aiFileTellProc =
+    ::std::option::Option<unsafe extern "C" fn(arg1: *mut aiFile) -> size_t>;
+pub type aiFileFlushProc =
+    ::std::option::Option<unsafe extern "C" fn(arg1: *mut aiFile)>;
+pub type aiFileSeek =
+    ::std::option::Option<unsafe extern "C" fn(arg1: *mut aiFile,
+                                                  arg2: size_t,
+                                                  arg3: aiOrigin)
+                              -> aiReturn>;
+pub type aiFileOpenProc =
+    ::std::option::Option<unsafe extern "C" fn(arg1: *mut aiFileIO,
+                                                  arg2: *const ::libc::c_char,
+                                                  arg3: *const ::libc::c_char)
+                              -> *mut aiFile>;
+pub type aiFileCloseProc =
+    ::std::option::Option<unsafe extern "C" fn(arg1: *mut aiFileIO,
+                                                  arg2: *mut aiFile)>;
+pub type aiUserData = *mut ::libc::c_char;
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[derive(Debug)]
+pub struct aiFileIO {
+    pub OpenProc: aiFileOpenProc,
+    pub CloseProc: aiFileCloseProc,
+    pub UserData: aiUserData,
+}
+impl ::std::default::Default for aiFileIO {
+    fn default() -> Self {
+        unsafe { ::std::mem::zeroed() }
+    }
+}
+#[repr(C)]
+#[derive(Copy, Clone)]
+#[derive(Debug)]
+pub struct aiFile {
+    pub ReadProc: aiFileReadProc,
+    pub WriteProc: aiFileWriteProc,
+    pub TellProc: aiFileTellProc,
+    pub FileSizeProc: aiFileTellProc,
+    pub SeekProc: aiFileSeek,
+    pub FlushProc: aiFileFlushProc,
+    pub UserData: aiUserData,
+}
+impl ::std::default::Default for aiFile {
+    fn default() -> Self {
+        unsafe { ::std::mem::zeroed() }
+    }
+}
 pub type aiLogStreamCallback =
     ::std::option::Option<unsafe extern "C" fn(arg1: *const ::libc::c_char,
                                                   arg2: *mut ::libc::c_char)>;
@@ -2008,6 +2059,11 @@ extern "C" {
     pub fn aiDetachAllLogStreams();
     pub fn aiReleaseImport(pScene: *const aiScene);
     pub fn aiGetErrorString() -> *const ::libc::c_char;
+    pub fn aiGetLegalString() -> *const ::libc::c_char;
+    pub fn aiGetVersionMinor() -> ::libc::c_uint;
+    pub fn aiGetVersionMajor() -> ::libc::c_uint;
+    pub fn aiGetVersionRevision() -> ::libc::c_uint;
+    pub fn aiGetCompileFlags() -> ::libc::c_uint;
     pub fn aiIsExtensionSupported(szExtension: *const ::libc::c_char) -> aiBool;
     pub fn aiGetExtensionList(szOut: *mut aiString);
     pub fn aiGetMemoryRequirements(pIn: *const aiScene, in_: *mut aiMemoryInfo);
@@ -0,0 +1,272 @@
+// Sizes and field offsets of the structs used by the safe wrapper, copied
+// by hand from the assimp 3.x C headers on 64-bit targets. This guards the
+// pregenerated bindings against accidental edits; it compares them to
+// these numbers, not to the headers or the library on the system. With
+// the `bindgen` feature, build.rs checks the regenerated bindings against
+// the installed headers instead, see `check_layout()` there.
+
+use super::*;
+
+macro_rules! assert_layout {
+    ($($ty:ident: $size:expr, { $($field:ident: $offset:expr,)* })*) => {
+        $(
+            const _: () = assert!(
+                ::std::mem::size_of::<$ty>() == $size,
+                concat!("size of ", stringify!($ty), " differs from assimp 3.x"),
+            );
+            $(
+                const _: () = assert!(
+                    ::std::mem::offset_of!($ty, $field) == $offset,
+                    concat!("offset of ", stringify!($ty), "::", stringify!($field), " differs from assimp 3.x"),
+                );
+            )*
+        )*
+    }
+}
+
+assert_layout! {
+    aiAnimMesh: 168, {
+        mVertices: 0,
+        mNormals: 8,
+        mTangents: 16,
+        mBitangents: 24,
+        mColors: 32,
+        mTextureCoords: 96,
+        mNumVertices: 160,
+    }
+    aiAnimation: 1080, {
+        mName: 0,
+        mDuration: 1032,
+        mTicksPerSecond: 1040,
+        mNumChannels: 1048,
+        mChannels: 1056,
+        mNumMeshChannels: 1064,
+        mMeshChannels: 1072,
+    }
+    aiBone: 1112, {
+        mName: 0,
+        mNumWeights: 1032,
+        mWeights: 1040,
+        mOffsetMatrix: 1048,
+    }
+    aiCamera: 1088, {
+        mName: 0,
+        mPosition: 1032,
+        mUp: 1044,
+        mLookAt: 1056,
+        mHorizontalFOV: 1068,
+        mClipPlaneNear: 1072,
+        mClipPlaneFar: 1076,
+        mAspect: 1080,
+    }
+    aiColor3D: 12, {
+        r: 0,
+        g: 4,
+        b: 8,
+    }
+    aiColor4D: 16, {
+        r: 0,
+        g: 4,
+        b: 8,
+        a: 12,
+    }
+    aiFace: 16, {
+        mNumIndices: 0,
+        mIndices: 8,
+    }
+    aiFile: 56, {
+        ReadProc: 0,
+        WriteProc: 8,
+        TellProc: 16,
+        FileSizeProc: 24,
+        SeekProc: 32,
+        FlushProc: 40,
+        UserData: 48,
+    }
+    aiFileIO: 24, {
+        OpenProc: 0,
+        CloseProc: 8,
+        UserData: 16,
+    }
+    aiImporterDesc: 64, {
+        mName: 0,
+        mAuthor: 8,
+        mMaintainer: 16,
+        mComments: 24,
+        mFlags: 32,
+        mMinMajor: 36,
+        mMinMinor: 40,
+        mMaxMajor: 44,
+        mMaxMinor: 48,
+        mFileExtensions: 56,
+    }
+    aiLight: 1136, {
+        mName: 0,
+        mType: 1032,
+        mPosition: 1036,
+        mDirection: 1048,
+        mUp: 1060,
+        mAttenuationConstant: 1072,
+        mAttenuationLinear: 1076,
+        mAttenuationQuadratic: 1080,
+        mColorDiffuse: 1084,
+        mColorSpecular: 1096,
+        mColorAmbient: 1108,
+        mAngleInnerCone: 1120,
+        mAngleOuterCone: 1124,
+        mSize: 1128,
+    }
+    aiMaterial: 16, {
+        mProperties: 0,
+        mNumProperties: 8,
+        mNumAllocated: 12,
+    }
+    aiMaterialProperty: 1056, {
+        mKey: 0,
+        mSemantic: 1032,
+        mIndex: 1036,
+        mDataLength: 1040,
+        mType: 1044,
+        mData: 1048,
+    }
+    aiMatrix4x4: 64, {
+        a1: 0,
+        a2: 4,
+        a3: 8,
+        a4: 12,
+        b1: 16,
+        b2: 20,
+        b3: 24,
+        b4: 28,
+        c1: 32,
+        c2: 36,
+        c3: 40,
+        c4: 44,
+        d1: 48,
+        d2: 52,
+        d3: 56,
+        d4: 60,
+    }
+    aiMesh: 1288, {
+        mPrimitiveTypes: 0,
+        mNumVertices: 4,
+        mNumFaces: 8,
+        mVertices: 16,
+        mNormals: 24,
+        mTangents: 32,
+        mBitangents: 40,
+        mColors: 48,
+        mTextureCoords: 112,
+        mNumUVComponents: 176,
+        mFaces: 208,
+        mNumBones: 216,
+        mBones: 224,
+        mMaterialIndex: 232,
+        mName: 240,
+        mNumAnimMeshes: 1272,
+        mAnimMeshes: 1280,
+    }
+    aiMeshAnim: 1048, {
+        mName: 0,
+        mNumKeys: 1032,
+        mKeys: 1040,
+    }
+    aiMeshKey: 16, {
+        mTime: 0,
+        mValue: 8,
+    }
+    aiMetadata: 24, {
+        mNumProperties: 0,
+        mKeys: 8,
+        mValues: 16,
+    }
+    aiMetadataEntry: 16, {
+        mType: 0,
+        mData: 8,
+    }
+    aiNode: 1144, {
+        mName: 0,
+        mTransformation: 1032,
+        mParent: 1096,
+        mNumChildren: 1104,
+        mChildren: 1112,
+        mNumMeshes: 1120,
+        mMeshes: 1128,
+        mMetaData: 1136,
+    }
+    aiNodeAnim: 1088, {
+        mNodeName: 0,
+        mNumPositionKeys: 1032,
+        mPositionKeys: 1040,
+        mNumRotationKeys: 1048,
+        mRotationKeys: 1056,
+        mNumScalingKeys: 1064,
+        mScalingKeys: 1072,
+        mPreState: 1080,
+        mPostState: 1084,
+    }
+    aiQuatKey: 24, {
+        mTime: 0,
+        mValue: 8,
+    }
+    aiQuaternion: 16, {
+        w: 0,
+        x: 4,
+        y: 8,
+        z: 12,
+    }
+    aiScene: 120, {
+        mFlags: 0,
+        mRootNode: 8,
+        mNumMeshes: 16,
+        mMeshes: 24,
+        mNumMaterials: 32,
+        mMaterials: 40,
+        mNumAnimations: 48,
+        mAnimations: 56,
+        mNumTextures: 64,
+        mTextures: 72,
+        mNumLights: 80,
+        mLights: 88,
+        mNumCameras: 96,
+        mCameras: 104,
+    }
+    aiString: 1032, {
+        length: 0,
+        data: 8,
+    }
+    aiTexel: 4, {
+        b: 0,
+        g: 1,
+        r: 2,
+        a: 3,
+    }
+    aiTexture: 24, {
+        mWidth: 0,
+        mHeight: 4,
+        achFormatHint: 8,
+        pcData: 16,
+    }
+    aiUVTransform: 20, {
+        mTranslation: 0,
+        mScaling: 8,
+        mRotation: 16,
+    }
+    aiVector2D: 8, {
+        x: 0,
+        y: 4,
+    }
+    aiVector3D: 12, {
+        x: 0,
+        y: 4,
+        z: 8,
+    }
+    aiVectorKey: 24, {
+        mTime: 0,
+        mValue: 8,
+    }
+    aiVertexWeight: 8, {
+        mVertexId: 0,
+        mWeight: 4,
+    }
+}
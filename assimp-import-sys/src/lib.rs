@@ -0,0 +1,46 @@
+//! Raw FFI bindings for assimp's C API.
+//!
+//! By default the pregenerated bindings for the assimp 3.x struct layout are
+//! used. With the `bindgen` feature enabled they are generated at build time
+//! from the system's headers instead (set `ASSIMP_INCLUDE_DIR` if they are
+//! not in a default include path), so the layout matches the installed
+//! headers.
+//!
+//! The size and field offsets of every struct the safe wrapper uses are
+//! asserted at compile time: against the assimp 3.x values for the
+//! pregenerated bindings (64-bit targets only), and against what the C
+//! compiler sees in the installed headers with `bindgen` (skipped when
+//! cross-compiling). Neither can verify the library that is actually
+//! linked at runtime; see `BINDINGS_VERSION_MAJOR` for the version check
+//! done before importing.
+
+#![allow(dead_code,
+         non_camel_case_types,
+         non_upper_case_globals,
+         non_snake_case)]
+
+extern crate libc;
+
+pub const AI_MAX_NUMBER_OF_COLOR_SETS: usize = 0x8;
+pub const AI_MAX_NUMBER_OF_TEXTURECOORDS: usize = 0x8;
+
+/// The assimp major version the pregenerated bindings were made for.
+///
+/// `None` with the `bindgen` feature, as the bindings then follow whatever
+/// headers are installed. Check this instead of the feature: features of
+/// this crate may be enabled by other crates in the dependency graph.
+#[cfg(not(feature = "bindgen"))]
+pub const BINDINGS_VERSION_MAJOR: Option<::libc::c_uint> = Some(3);
+#[cfg(feature = "bindgen")]
+pub const BINDINGS_VERSION_MAJOR: Option<::libc::c_uint> = None;
+
+#[cfg(not(feature = "bindgen"))]
+include!("bindings.rs");
+
+#[cfg(all(not(feature = "bindgen"), target_pointer_width = "64"))]
+mod layout;
+
+#[cfg(feature = "bindgen")]
+include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+#[cfg(feature = "bindgen")]
+include!(concat!(env!("OUT_DIR"), "/layout_check.rs"));
@@ -0,0 +1,6 @@
+#include <assimp/cfileio.h>
+#include <assimp/cimport.h>
+#include <assimp/cexport.h>
+#include <assimp/scene.h>
+#include <assimp/postprocess.h>
+#include <assimp/version.h>
@@ -1,5 +1,6 @@
 extern crate assimp_import as ai;
 
+use ai::prelude::*;
 use std::env;
 
 fn main() {
@@ -12,13 +13,13 @@ fn main() {
     };
 
     println!("=== Loading file '{}' ===", &file);
-    let scene = ai::Scene::from_file(&file, ai::PostProcessSteps::empty()).unwrap();
+    let scene = Scene::from_path(&file, PostProcessSteps::empty()).unwrap();
 
     println!("\n=== Loading successful ===");
     println!("Scene flags: {:?}", scene.flags());
 
     println!("\n=== Nodes ===");
-    fn print_node(node: &ai::Node, depth: usize, idx: usize) {
+    fn print_node(node: &Node, depth: usize, idx: usize) {
         let indent: String = (0..depth).map(|_| ' ').collect();
         println!("{}| Node #{}", indent, idx);
         println!("{}- Name:\t\t{:?}", indent, node.name());
@@ -28,7 +29,9 @@ fn main() {
             print_node(child, depth + 1, idx);
         }
     }
-    print_node(&scene.root_node(), 0, 0);
+    if let Some(root) = scene.root_node() {
+        print_node(&root, 0, 0);
+    }
 
     println!("\n=== Meshes ===");
     for (idx, mesh) in scene.meshes().iter().enumerate() {
@@ -66,7 +69,8 @@ fn main() {
 
         println!("- Faces:\t\t{}", mesh.faces().len());
         println!("- Bones:\t\t{}", mesh.bones().len());
-        println!("- Material Idx:\t\t{}", mesh.material_idx());
+        println!("- Anim Meshes:\t\t{}", mesh.anim_meshes().len());
+        println!("- Material:\t\t{:?}", mesh.material());
     }
 
     println!("\n=== Materials ===");
@@ -93,20 +97,21 @@ fn main() {
         println!("-- color_emissive: {:?}", props.color_emissive);
         println!("-- color_transparent: {:?}", props.color_transparent);
         println!("-- color_reflective: {:?}", props.color_reflective);
+        println!("-- alpha_mode: {:?}", mat.alpha_mode());
 
         let tex_tys = vec![
-            ai::TextureType::None, 
-            ai::TextureType::Diffuse,
-            ai::TextureType::Specular,
-            ai::TextureType::Ambient,
-            ai::TextureType::Emissive,
-            ai::TextureType::Height,
-            ai::TextureType::Normals,
-            ai::TextureType::Shininess,
-            ai::TextureType::Opacity,
-            ai::TextureType::Displacement,
-            ai::TextureType::Lightmap,
-            ai::TextureType::Reflection,
+            TextureType::None, 
+            TextureType::Diffuse,
+            TextureType::Specular,
+            TextureType::Ambient,
+            TextureType::Emissive,
+            TextureType::Height,
+            TextureType::Normals,
+            TextureType::Shininess,
+            TextureType::Opacity,
+            TextureType::Displacement,
+            TextureType::Lightmap,
+            TextureType::Reflection,
         ];
         for tex_ty in tex_tys {
             for idx2 in 0..mat.count_texture_properties(tex_ty) {
@@ -1,5 +1,10 @@
-use prim::{self, Quaternion, Vector3};
+use camera::Camera;
+use light::Light;
+use metadata::MetadataValue;
+use scene::Scene;
+use prim::{self, Matrix4, Quaternion, Vector3};
 use ffi;
+use std::collections::HashMap;
 
 // ++++++++++++++++++++ key prim ++++++++++++++++++++
 
@@ -142,6 +147,95 @@ impl<'a> NodeAnim<'a> {
     pub fn post_state(&self) -> AnimBehavior {
         unsafe { AnimBehavior::from_ffi(self.raw().mPostState) }
     }
+
+    /// The interpolated transformation at `time` (in ticks).
+    ///
+    /// Positions and scalings are interpolated linearly, rotations
+    /// spherically. Outside of the keyed range the first or last key is
+    /// held, regardless of `pre_state()` and `post_state()`.
+    pub fn sample(&self, time: f64) -> Transform {
+        let positions = self.position_keys();
+        let rotations = self.rotation_keys();
+        let scalings = self.scaling_keys();
+        Transform {
+            position: sample_keys(positions.len(), |i| positions[i].time(), time)
+                .map_or([0.0; 3], |(a, b, t)| prim::lerp(positions[a].value(), positions[b].value(), t)),
+            rotation: sample_keys(rotations.len(), |i| rotations[i].time(), time)
+                .map_or([1.0, 0.0, 0.0, 0.0], |(a, b, t)| {
+                    prim::quat_slerp(rotations[a].value(), rotations[b].value(), t)
+                }),
+            scaling: sample_keys(scalings.len(), |i| scalings[i].time(), time)
+                .map_or([1.0; 3], |(a, b, t)| prim::lerp(scalings[a].value(), scalings[b].value(), t)),
+        }
+    }
+}
+
+/// Finds the keys surrounding `time` and the interpolation factor between
+/// them, `None` if there are no keys.
+fn sample_keys<F: Fn(usize) -> f64>(len: usize, key_time: F, time: f64) -> Option<(usize, usize, f32)> {
+    if len == 0 {
+        return None;
+    }
+    if time <= key_time(0) {
+        return Some((0, 0, 0.0));
+    }
+    if time >= key_time(len - 1) {
+        return Some((len - 1, len - 1, 0.0));
+    }
+    // first key after `time`, keys are sorted
+    let (mut lo, mut hi) = (0, len - 1);
+    while hi - lo > 1 {
+        let mid = (lo + hi) / 2;
+        if key_time(mid) <= time {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    let span = key_time(hi) - key_time(lo);
+    let t = if span > 0.0 { (time - key_time(lo)) / span } else { 0.0 };
+    Some((lo, hi, t as f32))
+}
+
+// ++++++++++++++++++++ Transform ++++++++++++++++++++
+
+/// A decomposed node transformation, as produced by `NodeAnim::sample()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub position: Vector3,
+    pub rotation: Quaternion,
+    pub scaling: Vector3,
+}
+
+impl Transform {
+    /// The transformation matrix, applying scaling, rotation and
+    /// translation in this order.
+    pub fn to_matrix(&self) -> Matrix4 {
+        prim::mat4_from_trs(self.position, self.rotation, self.scaling)
+    }
+
+    /// Decomposes a transformation matrix without shearing, e.g. a node's
+    /// `transform()`.
+    pub fn from_matrix(m: &Matrix4) -> Transform {
+        let column = |c: usize| [m[0][c], m[1][c], m[2][c]];
+        let scaling = [prim::length(column(0)), prim::length(column(1)), prim::length(column(2))];
+        let mut rotation = prim::IDENTITY;
+        for row in 0..3 {
+            for col in 0..3 {
+                rotation[row][col] = if scaling[col] != 0.0 { m[row][col] / scaling[col] } else { 0.0 };
+            }
+        }
+        Transform { position: column(3), rotation: prim::quat_from_mat4(&rotation), scaling }
+    }
+
+    /// Interpolates between `self` (`t = 0`) and `other` (`t = 1`).
+    pub fn blend(&self, other: &Transform, t: f32) -> Transform {
+        Transform {
+            position: prim::lerp(self.position, other.position, t),
+            rotation: prim::quat_slerp(self.rotation, other.rotation, t),
+            scaling: prim::lerp(self.scaling, other.scaling, t),
+        }
+    }
 }
 
 // ++++++++++++++++++++ MeshAnim ++++++++++++++++++++
@@ -174,11 +268,458 @@ impl<'a> Animation<'a> {
         self.raw().mTicksPerSecond
     }
 
+    /// Duration of the animation in seconds.
+    ///
+    /// Animations without ticks per second are assumed to run at 25 ticks
+    /// per second, like assimp's own viewer does.
+    pub fn duration_seconds(&self) -> f64 {
+        self.duration() / self.effective_ticks_per_second()
+    }
+
+    fn effective_ticks_per_second(&self) -> f64 {
+        let tps = self.ticks_per_second();
+        if tps > 0.0 { tps } else { 25.0 }
+    }
+
     /// The node animation channels. Each channel affects a single node.
     /// The array is mNumChannels in size.
-    pub fn channels(&self) -> &[NodeAnim] {
+    pub fn channels(&self) -> &[NodeAnim<'_>] {
         unsafe { NodeAnim::slice(self.raw().mChannels, self.raw().mNumChannels) }
     }
 
     // TODO mesh_channels, see mesh.rs
 }
+
+// ++++++++++++++++++++ Player ++++++++++++++++++++
+
+/// How a `Player` continues after the end of an animation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlayMode {
+    /// Start over from the beginning.
+    Loop,
+    /// Play backwards to the beginning, then forwards again.
+    PingPong,
+    /// Hold the last frame.
+    Clamp,
+}
+
+impl PlayMode {
+    /// The play mode corresponding to a channel's `post_state()`.
+    pub fn from_behavior(behavior: AnimBehavior) -> PlayMode {
+        match behavior {
+            AnimBehavior::Repeat => PlayMode::Loop,
+            AnimBehavior::Default | AnimBehavior::Constant | AnimBehavior::Linear => PlayMode::Clamp,
+        }
+    }
+
+    /// Maps an unbounded playback time onto `[0, duration]`.
+    fn wrap(&self, time: f64, duration: f64) -> f64 {
+        if duration <= 0.0 {
+            return 0.0;
+        }
+        match *self {
+            PlayMode::Loop => time.rem_euclid(duration),
+            PlayMode::PingPong => {
+                let t = time.rem_euclid(2.0 * duration);
+                if t > duration { 2.0 * duration - t } else { t }
+            }
+            PlayMode::Clamp => time.max(0.0).min(duration),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Clip<'a> {
+    animation: &'a Animation<'a>,
+    mode: PlayMode,
+    /// Unbounded playback time in seconds, `None` until the clip was
+    /// advanced or seeked.
+    time: Option<f64>,
+}
+
+impl<'a> Clip<'a> {
+    fn new(animation: &'a Animation<'a>) -> Self {
+        Clip { animation, mode: default_mode(animation), time: None }
+    }
+
+    /// The playback time, a fresh clip starts at its end when playing
+    /// backwards.
+    fn time(&self, speed: f64) -> f64 {
+        self.time.unwrap_or(if speed < 0.0 { self.animation.duration_seconds() } else { 0.0 })
+    }
+
+    fn advance(&mut self, dt: f64, speed: f64) {
+        self.time = Some(self.time(speed) + dt * speed);
+    }
+
+    fn sample(&self, speed: f64, pose: &mut HashMap<&'a str, Transform>) {
+        let seconds = self.mode.wrap(self.time(speed), self.animation.duration_seconds());
+        let ticks = seconds * self.animation.effective_ticks_per_second();
+        for channel in self.animation.channels() {
+            pose.insert(channel.node_name(), channel.sample(ticks));
+        }
+    }
+}
+
+/// Frame-rate independent playback of animations.
+///
+/// Advance the player by the frame time with `advance()` and apply the
+/// node transformations from `sample()`, which replace the local
+/// transformations of the animated nodes.
+pub struct Player<'a> {
+    clip: Clip<'a>,
+    /// The clip faded out, with the elapsed and total fade time.
+    fade: Option<(Clip<'a>, f64, f64)>,
+    /// Playback speed factor, negative values play backwards.
+    pub speed: f64,
+}
+
+impl<'a> Player<'a> {
+    /// Starts playing `animation` from the beginning at normal speed.
+    /// If `speed` is made negative before the first `advance()`, playback
+    /// starts at the end instead.
+    ///
+    /// The play mode is derived from the post state of the animation's
+    /// first channel, see `PlayMode::from_behavior()`.
+    pub fn new(animation: &'a Animation<'a>) -> Self {
+        Player { clip: Clip::new(animation), fade: None, speed: 1.0 }
+    }
+
+    pub fn animation(&self) -> &'a Animation<'a> {
+        self.clip.animation
+    }
+
+    pub fn mode(&self) -> PlayMode {
+        self.clip.mode
+    }
+
+    pub fn set_mode(&mut self, mode: PlayMode) {
+        self.clip.mode = mode;
+    }
+
+    /// The current position within the animation, in seconds.
+    pub fn time(&self) -> f64 {
+        self.clip.mode.wrap(self.clip.time(self.speed), self.clip.animation.duration_seconds())
+    }
+
+    /// Jumps to the given position in seconds.
+    pub fn seek(&mut self, time: f64) {
+        self.clip.time = Some(time);
+    }
+
+    /// Whether a clamped animation has reached its end (or its beginning,
+    /// when playing backwards).
+    pub fn is_finished(&self) -> bool {
+        let duration = self.clip.animation.duration_seconds();
+        let time = self.clip.time(self.speed);
+        let at_end = if self.speed >= 0.0 { time >= duration } else { time <= 0.0 };
+        self.clip.mode == PlayMode::Clamp && at_end
+    }
+
+    /// Advances playback by `dt` seconds of real time.
+    pub fn advance(&mut self, dt: f64) {
+        self.clip.advance(dt, self.speed);
+        let mut done = false;
+        if let Some((ref mut from, ref mut elapsed, duration)) = self.fade {
+            from.advance(dt, self.speed);
+            *elapsed += dt;
+            done = *elapsed >= duration;
+        }
+        if done {
+            self.fade = None;
+        }
+    }
+
+    /// Switches to `animation`, blending from the current animation over
+    /// `duration` seconds. The new animation starts at its beginning (its
+    /// end when playing backwards).
+    pub fn cross_fade(&mut self, animation: &'a Animation<'a>, duration: f64) {
+        let from = self.clip;
+        self.clip = Clip::new(animation);
+        self.fade = if duration > 0.0 { Some((from, 0.0, duration)) } else { None };
+    }
+
+    /// The local transformations of all animated nodes, by node name.
+    ///
+    /// During a cross-fade, nodes animated by both animations are
+    /// blended. Nodes animated by only one of them are blended with their
+    /// rest transformation in `scene`, so they fade out with the old and
+    /// in with the new animation.
+    pub fn sample(&self, scene: &Scene) -> HashMap<&'a str, Transform> {
+        let mut pose = HashMap::new();
+        self.clip.sample(self.speed, &mut pose);
+        if let Some((from, elapsed, duration)) = self.fade {
+            let mut old = HashMap::new();
+            from.sample(self.speed, &mut old);
+            let t = (elapsed / duration) as f32;
+            let rest = |name: &str| scene.find_node(name).map(|n| Transform::from_matrix(&n.transform()));
+            for (name, new) in pose.iter_mut() {
+                if !old.contains_key(name) {
+                    if let Some(rest) = rest(name) {
+                        *new = rest.blend(new, t);
+                    }
+                }
+            }
+            for (name, transform) in old {
+                let blended = match pose.get(name) {
+                    Some(new) => transform.blend(new, t),
+                    None => match rest(name) {
+                        Some(rest) => transform.blend(&rest, t),
+                        None => transform,
+                    },
+                };
+                pose.insert(name, blended);
+            }
+        }
+        pose
+    }
+}
+
+fn default_mode(animation: &Animation) -> PlayMode {
+    animation.channels().first().map_or(PlayMode::Clamp, |c| PlayMode::from_behavior(c.post_state()))
+}
+
+// ++++++++++++++++++++ typed tracks ++++++++++++++++++++
+
+/// The animation of a camera's node.
+#[derive(Clone, Copy)]
+pub struct CameraTrack<'a> {
+    pub animation: &'a Animation<'a>,
+    pub camera: &'a Camera<'a>,
+    /// The channel animating the camera's node, if any.
+    pub channel: Option<&'a NodeAnim<'a>>,
+    /// The channel animating the `<name>.Target` node some formats
+    /// (3DS, ASE) use for the look-at point, if any.
+    pub target: Option<&'a NodeAnim<'a>>,
+}
+
+/// The animation of a light's node.
+#[derive(Clone, Copy)]
+pub struct LightTrack<'a> {
+    pub animation: &'a Animation<'a>,
+    pub light: &'a Light<'a>,
+    /// The channel animating the light's node, if any.
+    pub channel: Option<&'a NodeAnim<'a>>,
+    /// The channel animating the `<name>.Target` node of spot lights, if any.
+    pub target: Option<&'a NodeAnim<'a>>,
+}
+
+/// Camera and light animations of a scene, see `typed_tracks()`.
+#[derive(Clone, Default)]
+pub struct TypedTracks<'a> {
+    pub cameras: Vec<CameraTrack<'a>>,
+    pub lights: Vec<LightTrack<'a>>,
+}
+
+/// Pairs the animation channels of a scene with the cameras and lights
+/// they drive.
+///
+/// Cameras and lights are attached to the node of the same name, so a
+/// track is emitted for every animation animating such a node (or its
+/// target node). Assimp only animates node transformations; parameters
+/// like the field of view or the light color are constant and taken from
+/// the `Camera`/`Light` itself, as with `AnimBehavior::Default`.
+pub fn typed_tracks(scene: &Scene) -> TypedTracks<'_> {
+    let mut ret = TypedTracks::default();
+    for animation in scene.animations() {
+        let find = |name: &str| animation.channels().iter().find(|c| c.node_name() == name);
+        let find_target = |name: &str| {
+            let target = format!("{}.Target", name);
+            animation.channels().iter().find(|c| c.node_name() == target)
+        };
+
+        for camera in scene.cameras() {
+            let (channel, target) = (find(camera.name()), find_target(camera.name()));
+            if channel.is_some() || target.is_some() {
+                ret.cameras.push(CameraTrack { animation, camera, channel, target });
+            }
+        }
+        for light in scene.lights() {
+            let (channel, target) = (find(light.name()), find_target(light.name()));
+            if channel.is_some() || target.is_some() {
+                ret.lights.push(LightTrack { animation, light, channel, target });
+            }
+        }
+    }
+    ret
+}
+
+// ++++++++++++++++++++ events ++++++++++++++++++++
+
+/// A named point in time of an animation, e.g. a footstep or hit frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimEvent {
+    /// Time of the event in seconds.
+    pub time: f64,
+    pub name: String,
+    /// Extra data attached to the event, if the convention supports it.
+    pub payload: Option<String>,
+}
+
+/// A convention for storing animation events in a scene, see `anim_events()`.
+pub trait AnimEventExtractor {
+    /// The events of `animation`, in any order.
+    fn extract(&self, scene: &Scene, animation: &Animation) -> Vec<AnimEvent>;
+}
+
+/// Events stored as marker nodes: every position key of a channel
+/// animating a node named `<prefix><event name>` is an event.
+///
+/// This is what most DCC tools export for dummy/locator objects keyed at
+/// the event frames.
+#[derive(Debug, Clone)]
+pub struct MarkerTracks {
+    pub prefix: String,
+}
+
+impl Default for MarkerTracks {
+    fn default() -> Self {
+        MarkerTracks { prefix: "event_".to_owned() }
+    }
+}
+
+impl AnimEventExtractor for MarkerTracks {
+    fn extract(&self, _scene: &Scene, animation: &Animation) -> Vec<AnimEvent> {
+        let tps = animation.effective_ticks_per_second();
+        let mut ret = Vec::new();
+        for channel in animation.channels() {
+            let name = channel.node_name();
+            if !name.starts_with(&self.prefix[..]) || name.len() == self.prefix.len() {
+                continue;
+            }
+            for key in channel.position_keys() {
+                ret.push(AnimEvent { time: key.time() / tps, name: name[self.prefix.len()..].to_owned(), payload: None });
+            }
+        }
+        ret
+    }
+}
+
+/// Events stored in node metadata: a string entry with the key
+/// `<prefix><animation name>` holds `;` separated events of the form
+/// `<seconds>:<name>[:<payload>]`, e.g. `0.4:footstep_l;0.9:footstep_r`.
+///
+/// FBX exports custom properties of objects this way.
+#[derive(Debug, Clone)]
+pub struct MetadataEvents {
+    pub prefix: String,
+}
+
+impl Default for MetadataEvents {
+    fn default() -> Self {
+        MetadataEvents { prefix: "events:".to_owned() }
+    }
+}
+
+impl AnimEventExtractor for MetadataEvents {
+    fn extract(&self, scene: &Scene, animation: &Animation) -> Vec<AnimEvent> {
+        let key = format!("{}{}", self.prefix, animation.name().unwrap_or(""));
+        let mut ret = Vec::new();
+        for found in scene.query_metadata(&key) {
+            if found.key != key {
+                continue;
+            }
+            let value = match found.value {
+                MetadataValue::String(value) => value,
+                _ => continue,
+            };
+            for entry in value.split(';') {
+                let mut parts = entry.trim().splitn(3, ':');
+                let time = parts.next().and_then(|t| t.trim().parse::<f64>().ok());
+                let name = parts.next().map(str::trim).filter(|n| !n.is_empty());
+                if let (Some(time), Some(name)) = (time, name) {
+                    let payload = parts.next().map(|p| p.to_owned());
+                    ret.push(AnimEvent { time, name: name.to_owned(), payload });
+                }
+            }
+        }
+        ret
+    }
+}
+
+/// The events of every animation of `scene`, indexed like
+/// `Scene::animations()` and sorted by time.
+///
+/// The events found by all `extractors` are combined. Pass e.g.
+/// `&[&MarkerTracks::default(), &MetadataEvents::default()]` for the
+/// built-in conventions.
+pub fn anim_events(scene: &Scene, extractors: &[&dyn AnimEventExtractor]) -> Vec<Vec<AnimEvent>> {
+    scene
+        .animations()
+        .iter()
+        .map(|animation| {
+            let mut events: Vec<AnimEvent> =
+                extractors.iter().flat_map(|e| e.extract(scene, animation)).collect();
+            events.sort_by(|a, b| a.time.partial_cmp(&b.time).unwrap_or(::std::cmp::Ordering::Equal));
+            events
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_keys_bounds() {
+        let times = [0.0, 1.0, 3.0, 4.0];
+        let at = |t| sample_keys(times.len(), |i| times[i], t);
+        assert_eq!(sample_keys(0, |_| 0.0, 1.0), None);
+        assert_eq!(at(-1.0), Some((0, 0, 0.0)));
+        assert_eq!(at(0.0), Some((0, 0, 0.0)));
+        assert_eq!(at(5.0), Some((3, 3, 0.0)));
+        assert_eq!(at(0.5), Some((0, 1, 0.5)));
+        assert_eq!(at(2.5), Some((1, 2, 0.75)));
+        assert_eq!(at(3.0), Some((2, 3, 0.0)));
+        assert_eq!(sample_keys(1, |_| 2.0, 3.0), Some((0, 0, 0.0)));
+    }
+
+    #[test]
+    fn play_mode_wrap() {
+        assert_eq!(PlayMode::Loop.wrap(12.0, 10.0), 2.0);
+        assert_eq!(PlayMode::Loop.wrap(-2.0, 10.0), 8.0);
+        assert_eq!(PlayMode::PingPong.wrap(12.0, 10.0), 8.0);
+        assert_eq!(PlayMode::PingPong.wrap(-2.0, 10.0), 2.0);
+        assert_eq!(PlayMode::PingPong.wrap(22.0, 10.0), 2.0);
+        assert_eq!(PlayMode::Clamp.wrap(12.0, 10.0), 10.0);
+        assert_eq!(PlayMode::Clamp.wrap(-2.0, 10.0), 0.0);
+        assert_eq!(PlayMode::Loop.wrap(5.0, 0.0), 0.0);
+    }
+
+    fn animation(duration: f64) -> ffi::aiAnimation {
+        let mut raw: ffi::aiAnimation = unsafe { ::std::mem::zeroed() };
+        raw.mDuration = duration;
+        raw.mTicksPerSecond = 1.0;
+        raw
+    }
+
+    #[test]
+    fn player_reverse_starts_at_end() {
+        let mut raw = animation(10.0);
+        let animation = unsafe { Animation::from_ptr(&mut raw) };
+        let mut player = Player::new(&animation);
+        assert_eq!(player.mode(), PlayMode::Clamp);
+        player.speed = -1.0;
+        assert_eq!(player.time(), 10.0);
+        assert!(!player.is_finished());
+        player.advance(4.0);
+        assert_eq!(player.time(), 6.0);
+        player.advance(6.0);
+        assert_eq!(player.time(), 0.0);
+        assert!(player.is_finished());
+    }
+
+    #[test]
+    fn player_forward() {
+        let mut raw = animation(10.0);
+        let animation = unsafe { Animation::from_ptr(&mut raw) };
+        let mut player = Player::new(&animation);
+        assert_eq!(player.time(), 0.0);
+        player.advance(10.0);
+        assert!(player.is_finished());
+        player.set_mode(PlayMode::Loop);
+        player.advance(2.5);
+        assert_eq!(player.time(), 2.5);
+        assert!(!player.is_finished());
+    }
+}
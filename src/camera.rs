@@ -1,5 +1,7 @@
 use prim::{self, Vector3};
+use scene::Scene;
 use ffi;
+use std::collections::HashMap;
 
 ai_ptr_type!{
     /// Helper structure to describe a virtual camera.
@@ -123,3 +125,183 @@ impl<'a> Camera<'a> {
         self.raw().mAspect
     }
 }
+
+// ++++++++++++++++++++ StereoCamera ++++++++++++++++++++
+
+/// A pair of cameras forming a stereo rig, see `stereo_cameras()`.
+#[derive(Clone, Copy)]
+pub struct StereoCamera<'a> {
+    pub left: &'a Camera<'a>,
+    pub right: &'a Camera<'a>,
+    /// The interpupillary distance, i.e. the world space distance between
+    /// both cameras in bind pose.
+    pub ipd: f32,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Eye {
+    Left,
+    Right,
+}
+
+/// Finds the stereo camera rigs of a scene.
+///
+/// Names are split into tokens at `_`, `.`, `-`, whitespace and camelCase
+/// boundaries, and compared case-insensitively. Cameras are paired if
+/// their names only differ in a `left`/`l` vs. `right`/`r` token.
+/// Remaining cameras are paired if they are the only two cameras below
+/// the same parent node (other than the root node) and either carry
+/// opposite eye tokens or the parent's name has a `stereo` token. Without
+/// eye tokens, the camera further in -x direction is the left eye.
+pub fn stereo_cameras(scene: &Scene) -> Vec<StereoCamera<'_>> {
+    let cameras = scene.cameras();
+    let world_position = |camera: &Camera| {
+        let transform = scene.find_node(camera.name()).map_or(prim::IDENTITY, |n| n.global_transform());
+        prim::transform_point(&transform, camera.position())
+    };
+
+    // (left, right) camera indices
+    let mut pairs: Vec<(usize, usize)> = Vec::new();
+
+    let mut by_key: HashMap<String, [Option<usize>; 2]> = HashMap::new();
+    for (idx, camera) in cameras.iter().enumerate() {
+        if let Some((eye, key)) = eye_key(camera.name()) {
+            let slots = by_key.entry(key).or_insert([None, None]);
+            let slot = &mut slots[if eye == Eye::Left { 0 } else { 1 }];
+            if slot.is_none() {
+                *slot = Some(idx);
+            }
+        }
+    }
+    let mut named: Vec<(usize, usize)> = by_key
+        .values()
+        .filter_map(|slots| match *slots {
+            [Some(left), Some(right)] => Some((left, right)),
+            _ => None,
+        })
+        .collect();
+    named.sort();
+    pairs.extend(named);
+
+    let mut by_parent: HashMap<*mut ffi::aiNode, Vec<usize>> = HashMap::new();
+    let mut stereo_parents: Vec<*mut ffi::aiNode> = Vec::new();
+    for (idx, camera) in cameras.iter().enumerate() {
+        let parent = scene.find_node(camera.name()).and_then(|n| n.parent());
+        // everything is below the root, sharing it says nothing
+        if let Some(parent) = parent.filter(|p| p.parent().is_some()) {
+            if tokens(parent.name().unwrap_or("")).iter().any(|t| t == "stereo") {
+                stereo_parents.push(parent.as_ptr());
+            }
+            by_parent.entry(parent.as_ptr()).or_insert_with(Vec::new).push(idx);
+        }
+    }
+    let mut siblings: Vec<(bool, Vec<usize>)> = by_parent
+        .into_iter()
+        .filter(|&(_, ref v)| v.len() == 2)
+        .map(|(parent, v)| (stereo_parents.contains(&parent), v))
+        .collect();
+    siblings.sort();
+    for (stereo_parent, pair) in siblings {
+        let (a, b) = (pair[0], pair[1]);
+        if pairs.iter().any(|&(l, r)| l == a || l == b || r == a || r == b) {
+            continue;
+        }
+        let eyes = (eye_key(cameras[a].name()).map(|e| e.0), eye_key(cameras[b].name()).map(|e| e.0));
+        match eyes {
+            (Some(Eye::Left), Some(Eye::Right)) => pairs.push((a, b)),
+            (Some(Eye::Right), Some(Eye::Left)) => pairs.push((b, a)),
+            _ if !stereo_parent => {}
+            _ if world_position(&cameras[a])[0] <= world_position(&cameras[b])[0] => pairs.push((a, b)),
+            _ => pairs.push((b, a)),
+        }
+    }
+
+    pairs
+        .into_iter()
+        .map(|(left, right)| {
+            let offset = prim::sub(world_position(&cameras[right]), world_position(&cameras[left]));
+            StereoCamera { left: &cameras[left], right: &cameras[right], ipd: prim::length(offset) }
+        })
+        .collect()
+}
+
+/// Splits a camera name into the eye it denotes and its tokens with the
+/// eye token replaced, so both eyes of a rig yield the same key.
+fn eye_key(name: &str) -> Option<(Eye, String)> {
+    let mut tokens = tokens(name);
+    for token in &mut tokens {
+        let eye = match &token[..] {
+            "left" | "l" => Eye::Left,
+            "right" | "r" => Eye::Right,
+            _ => continue,
+        };
+        *token = "*".to_owned();
+        return Some((eye, tokens.join("|")));
+    }
+    None
+}
+
+/// Lowercase tokens of a name, split at `_`, `.`, `-`, whitespace and
+/// camelCase boundaries (`camLeft`, `Cam01Left`).
+fn tokens(name: &str) -> Vec<String> {
+    let mut ret = Vec::new();
+    let mut current = String::new();
+    let mut prev: Option<char> = None;
+    for c in name.chars() {
+        if c == '_' || c == '.' || c == '-' || c.is_whitespace() {
+            if !current.is_empty() {
+                ret.push(current.to_lowercase());
+                current.clear();
+            }
+            prev = None;
+            continue;
+        }
+        let boundary = c.is_uppercase() && prev.map_or(false, |p| p.is_lowercase() || p.is_numeric());
+        if boundary && !current.is_empty() {
+            ret.push(current.to_lowercase());
+            current.clear();
+        }
+        current.push(c);
+        prev = Some(c);
+    }
+    if !current.is_empty() {
+        ret.push(current.to_lowercase());
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_split_at_separators_and_case() {
+        assert_eq!(tokens("Cam01Left"), vec!["cam01", "left"]);
+        assert_eq!(tokens("camLeft"), vec!["cam", "left"]);
+        assert_eq!(tokens("stereo_cam.R"), vec!["stereo", "cam", "r"]);
+        assert_eq!(tokens("main - cam"), vec!["main", "cam"]);
+        assert!(tokens("").is_empty());
+    }
+
+    #[test]
+    fn eye_key_needs_a_whole_token() {
+        assert!(eye_key("bright_cam").is_none());
+        assert!(eye_key("Copyright").is_none());
+        assert!(eye_key("Camera").is_none());
+    }
+
+    #[test]
+    fn eye_key_pairs() {
+        let (left, left_key) = eye_key("CamLeft").unwrap();
+        let (right, right_key) = eye_key("CamRight").unwrap();
+        assert!(left == Eye::Left && right == Eye::Right);
+        assert_eq!(left_key, right_key);
+
+        let (left, left_key) = eye_key("cam_L").unwrap();
+        let (right, right_key) = eye_key("cam_R").unwrap();
+        assert!(left == Eye::Left && right == Eye::Right);
+        assert_eq!(left_key, right_key);
+
+        assert_ne!(eye_key("rig_a_L").unwrap().1, eye_key("rig_b_R").unwrap().1);
+    }
+}
@@ -0,0 +1,219 @@
+//! Importer and post-processing configuration, see `PropertyStore`.
+//!
+//! The constants are the property names from assimp's `config.h` (without
+//! the `AI_CONFIG_` prefix). Refer to it for the full list and the exact
+//! semantics of each property.
+
+use prim::{self, Matrix4};
+use ffi;
+use std::ffi::CString;
+use libc::{c_float, c_int};
+
+/// Maximum bone count per mesh for the SplitByBoneCount step.
+pub const PP_SBBC_MAX_BONES: &'static str = "PP_SBBC_MAX_BONES";
+
+/// Maximum angle between two face normals at a vertex for
+/// CalcTangentSpace to smooth them, in degrees (float).
+pub const PP_CT_MAX_SMOOTHING_ANGLE: &'static str = "PP_CT_MAX_SMOOTHING_ANGLE";
+
+/// Maximum angle between two face normals at a vertex for GenSmoothNormals
+/// to smooth them, in degrees (float).
+pub const PP_GSN_MAX_SMOOTHING_ANGLE: &'static str = "PP_GSN_MAX_SMOOTHING_ANGLE";
+
+/// Materials the RemoveRedundantMaterials step must keep, as a
+/// whitespace separated list of names (string).
+pub const PP_RRM_EXCLUDE_LIST: &'static str = "PP_RRM_EXCLUDE_LIST";
+
+/// Whether PreTransformVertices keeps the scene hierarchy (bool).
+pub const PP_PTV_KEEP_HIERARCHY: &'static str = "PP_PTV_KEEP_HIERARCHY";
+
+/// Whether PreTransformVertices normalizes all vertices into the
+/// [-1, 1] range (bool).
+pub const PP_PTV_NORMALIZE: &'static str = "PP_PTV_NORMALIZE";
+
+/// Whether FindDegenerates removes degenerate primitives instead of
+/// converting them to points and lines (bool).
+pub const PP_FD_REMOVE: &'static str = "PP_FD_REMOVE";
+
+/// Nodes the OptimizeGraph step must keep, as a whitespace separated list
+/// of names (string).
+pub const PP_OG_EXCLUDE_LIST: &'static str = "PP_OG_EXCLUDE_LIST";
+
+/// Maximum number of triangles per mesh for SplitLargeMeshes (integer).
+pub const PP_SLM_TRIANGLE_LIMIT: &'static str = "PP_SLM_TRIANGLE_LIMIT";
+
+/// Maximum number of vertices per mesh for SplitLargeMeshes (integer).
+pub const PP_SLM_VERTEX_LIMIT: &'static str = "PP_SLM_VERTEX_LIMIT";
+
+/// Maximum number of bone weights per vertex for LimitBoneWeights
+/// (integer).
+pub const PP_LBW_MAX_WEIGHTS: &'static str = "PP_LBW_MAX_WEIGHTS";
+
+/// Threshold below which Debone considers a bone unnecessary (float).
+pub const PP_DB_THRESHOLD: &'static str = "PP_DB_THRESHOLD";
+
+/// Whether Debone only removes bones if all of them can be removed (bool).
+pub const PP_DB_ALL_OR_NONE: &'static str = "PP_DB_ALL_OR_NONE";
+
+/// Size of the post-transform vertex cache ImproveCacheLocality optimizes
+/// for (integer).
+pub const PP_ICL_PTCACHE_SIZE: &'static str = "PP_ICL_PTCACHE_SIZE";
+
+/// Components the RemoveComponent step removes, a combination of the
+/// `COMPONENT_*` flags (integer).
+pub const PP_RVC_FLAGS: &'static str = "PP_RVC_FLAGS";
+
+// `aiComponent` flags for `PP_RVC_FLAGS`.
+pub const COMPONENT_NORMALS: i32 = 0x2;
+pub const COMPONENT_TANGENTS_AND_BITANGENTS: i32 = 0x4;
+/// All vertex color channels.
+pub const COMPONENT_COLORS: i32 = 0x8;
+/// All texture coordinate channels.
+pub const COMPONENT_TEXCOORDS: i32 = 0x10;
+/// Bone weights, the bones' nodes stay in the hierarchy.
+pub const COMPONENT_BONEWEIGHTS: i32 = 0x20;
+pub const COMPONENT_ANIMATIONS: i32 = 0x40;
+/// Embedded textures.
+pub const COMPONENT_TEXTURES: i32 = 0x80;
+pub const COMPONENT_LIGHTS: i32 = 0x100;
+pub const COMPONENT_CAMERAS: i32 = 0x200;
+pub const COMPONENT_MESHES: i32 = 0x400;
+/// Materials, a default material is added afterwards.
+pub const COMPONENT_MATERIALS: i32 = 0x800;
+
+/// Primitive types the SortByPType step removes, a combination of
+/// `PrimitiveTypes` flags (integer).
+pub const PP_SBP_REMOVE: &'static str = "PP_SBP_REMOVE";
+
+/// Accuracy of FindInvalidData's animation key comparisons (float).
+pub const PP_FID_ANIM_ACCURACY: &'static str = "PP_FID_ANIM_ACCURACY";
+
+/// UV transformations TransformUVCoords evaluates, a combination of
+/// `aiUVTRAFO` flags (integer).
+pub const PP_TUV_EVALUATE: &'static str = "PP_TUV_EVALUATE";
+
+/// Prefer import speed over quality where importers have the choice
+/// (bool).
+pub const FAVOUR_SPEED: &'static str = "FAVOUR_SPEED";
+
+/// Whether importers log the time each step takes (bool).
+pub const GLOB_MEASURE_TIME: &'static str = "GLOB_MEASURE_TIME";
+
+/// The keyframe of animated formats (MD2, MD3, MDL, ...) to import as
+/// static mesh (integer).
+pub const IMPORT_GLOBAL_KEYFRAME: &'static str = "IMPORT_GLOBAL_KEYFRAME";
+
+/// Suppresses the dummy meshes importers generate for skeletons without
+/// geometry (bool).
+pub const IMPORT_NO_SKELETON_MESHES: &'static str = "IMPORT_NO_SKELETON_MESHES";
+
+/// Whether the FBX importer reads all geometry layers (bool).
+pub const IMPORT_FBX_READ_ALL_GEOMETRY_LAYERS: &'static str = "IMPORT_FBX_READ_ALL_GEOMETRY_LAYERS";
+
+/// Whether the FBX importer reads all materials, including unused ones
+/// (bool).
+pub const IMPORT_FBX_READ_ALL_MATERIALS: &'static str = "IMPORT_FBX_READ_ALL_MATERIALS";
+
+/// Whether the FBX importer reads materials (bool).
+pub const IMPORT_FBX_READ_MATERIALS: &'static str = "IMPORT_FBX_READ_MATERIALS";
+
+/// Whether the FBX importer reads cameras (bool).
+pub const IMPORT_FBX_READ_CAMERAS: &'static str = "IMPORT_FBX_READ_CAMERAS";
+
+/// Whether the FBX importer reads lights (bool).
+pub const IMPORT_FBX_READ_LIGHTS: &'static str = "IMPORT_FBX_READ_LIGHTS";
+
+/// Whether the FBX importer reads animations (bool).
+pub const IMPORT_FBX_READ_ANIMATIONS: &'static str = "IMPORT_FBX_READ_ANIMATIONS";
+
+/// Whether the FBX importer rejects files not strictly following the
+/// specification (bool).
+pub const IMPORT_FBX_STRICT_MODE: &'static str = "IMPORT_FBX_STRICT_MODE";
+
+/// Whether the FBX importer keeps pivot points as separate helper nodes
+/// (bool).
+pub const IMPORT_FBX_PRESERVE_PIVOTS: &'static str = "IMPORT_FBX_PRESERVE_PIVOTS";
+
+/// Whether the FBX importer drops animation curves with constant values
+/// (bool).
+pub const IMPORT_FBX_OPTIMIZE_EMPTY_ANIMATION_CURVES: &'static str = "IMPORT_FBX_OPTIMIZE_EMPTY_ANIMATION_CURVES";
+
+/// Whether the MD5 importer skips loading the .md5anim file of the same
+/// name (bool).
+pub const IMPORT_MD5_NO_ANIM_AUTOLOAD: &'static str = "IMPORT_MD5_NO_ANIM_AUTOLOAD";
+
+/// Whether the IFC importer skips `IfcSpace` representations (bool).
+pub const IMPORT_IFC_SKIP_SPACE_REPRESENTATIONS: &'static str = "IMPORT_IFC_SKIP_SPACE_REPRESENTATIONS";
+
+/// Whether the Collada importer ignores the file's up direction (bool).
+pub const IMPORT_COLLADA_IGNORE_UP_DIRECTION: &'static str = "IMPORT_COLLADA_IGNORE_UP_DIRECTION";
+
+/// A set of configuration properties for importing.
+///
+/// Properties are passed to the importers and post-processing steps,
+/// which read the ones they know and ignore the others. Property names
+/// containing NUL bytes are ignored.
+pub struct PropertyStore {
+    ptr: *mut ffi::aiPropertyStore,
+}
+
+impl Drop for PropertyStore {
+    fn drop(&mut self) {
+        unsafe { ffi::aiReleasePropertyStore(self.ptr) }
+    }
+}
+
+impl Default for PropertyStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PropertyStore {
+    /// An empty property store, every property has its default value.
+    pub fn new() -> Self {
+        let ptr = unsafe { ffi::aiCreatePropertyStore() };
+        assert!(!ptr.is_null());
+        PropertyStore { ptr }
+    }
+
+    pub fn set_int(&mut self, name: &str, value: i32) -> &mut Self {
+        if let Ok(name) = CString::new(name) {
+            unsafe { ffi::aiSetImportPropertyInteger(self.ptr, name.as_ptr(), value as c_int) }
+        }
+        self
+    }
+
+    /// Boolean properties are stored as integers.
+    pub fn set_bool(&mut self, name: &str, value: bool) -> &mut Self {
+        self.set_int(name, value as i32)
+    }
+
+    pub fn set_float(&mut self, name: &str, value: f32) -> &mut Self {
+        if let Ok(name) = CString::new(name) {
+            unsafe { ffi::aiSetImportPropertyFloat(self.ptr, name.as_ptr(), value as c_float) }
+        }
+        self
+    }
+
+    pub fn set_string(&mut self, name: &str, value: &str) -> &mut Self {
+        if let Ok(name) = CString::new(name) {
+            let value = prim::to_ai_string(value);
+            unsafe { ffi::aiSetImportPropertyString(self.ptr, name.as_ptr(), &value) }
+        }
+        self
+    }
+
+    pub fn set_matrix(&mut self, name: &str, value: &Matrix4) -> &mut Self {
+        if let Ok(name) = CString::new(name) {
+            let value = prim::to_ai_mat4(value);
+            unsafe { ffi::aiSetImportPropertyMatrix(self.ptr, name.as_ptr(), &value) }
+        }
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn as_ptr(&self) -> *const ffi::aiPropertyStore {
+        self.ptr
+    }
+}
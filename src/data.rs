@@ -0,0 +1,694 @@
+//! An owned copy of an imported scene, see `SceneData`.
+//!
+//! The wrapper types in the other modules borrow from the `aiScene` and
+//! can't outlive the `Scene`. The types here own all of their data, so
+//! they can be kept after the `Scene` was dropped, stored in caches or
+//! moved to other threads. They mirror the wrappers field by field, see
+//! there for the meaning of each field.
+
+use anim::{AnimBehavior, Animation, NodeAnim};
+use camera::Camera;
+use light::{Light, LightSourceType};
+use material::{Material, MaterialProperties, TextureProperties, TextureType};
+use mesh::{Bone, MaterialHandle, Mesh, PrimitiveTypes, VertexIdx};
+use metadata::MetadataValue;
+use scene::{MeshHandle, Node, Scene, SceneFlags};
+use texture::Texture;
+use prim::{Color3, Color4, Matrix4, Quaternion, Vector2, Vector3};
+use {MAX_COLOR_SETS, MAX_TEXTURE_COORDS};
+use std::ops::Range;
+
+// ++++++++++++++++++++ SceneData ++++++++++++++++++++
+
+/// Owned counterpart of `Scene`.
+#[derive(Debug, Clone)]
+pub struct SceneData {
+    pub flags: SceneFlags,
+    /// The root of the node hierarchy, `None` for scenes without one.
+    pub root: Option<NodeData>,
+    pub meshes: Vec<MeshData>,
+    pub materials: Vec<MaterialData>,
+    pub animations: Vec<AnimationData>,
+    pub textures: Vec<TextureData>,
+    pub lights: Vec<LightData>,
+    pub cameras: Vec<CameraData>,
+}
+
+impl SceneData {
+    /// Deep copies all data of `scene`.
+    pub fn from_scene(scene: &Scene) -> SceneData {
+        SceneData {
+            flags: scene.flags(),
+            root: scene.root_node().map(|root| NodeData::from_node(&root)),
+            meshes: scene.meshes().iter().map(MeshData::from_mesh).collect(),
+            materials: scene.materials().iter().map(MaterialData::from_material).collect(),
+            animations: scene.animations().iter().map(AnimationData::from_animation).collect(),
+            textures: scene.textures().iter().map(TextureData::from_texture).collect(),
+            lights: scene.lights().iter().map(LightData::from_light).collect(),
+            cameras: scene.cameras().iter().map(CameraData::from_camera).collect(),
+        }
+    }
+
+    /// The mesh referred to by `handle`, `None` if it's out of range.
+    pub fn mesh(&self, handle: MeshHandle) -> Option<&MeshData> {
+        self.meshes.get(handle.idx())
+    }
+
+    /// The material referred to by `handle`, `None` if it's out of range.
+    pub fn material(&self, handle: MaterialHandle) -> Option<&MaterialData> {
+        self.materials.get(handle.idx())
+    }
+}
+
+// ++++++++++++++++++++ NodeData ++++++++++++++++++++
+
+/// Owned counterpart of `MetadataValue`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataValueData {
+    Bool(bool),
+    I32(i32),
+    U64(u64),
+    F32(f32),
+    Vector3(Vector3),
+    String(String),
+}
+
+impl<'a> From<MetadataValue<'a>> for MetadataValueData {
+    fn from(value: MetadataValue<'a>) -> Self {
+        match value {
+            MetadataValue::Bool(v) => MetadataValueData::Bool(v),
+            MetadataValue::I32(v) => MetadataValueData::I32(v),
+            MetadataValue::U64(v) => MetadataValueData::U64(v),
+            MetadataValue::F32(v) => MetadataValueData::F32(v),
+            MetadataValue::Vector3(v) => MetadataValueData::Vector3(v),
+            MetadataValue::String(v) => MetadataValueData::String(v.to_owned()),
+        }
+    }
+}
+
+/// Owned counterpart of `Node`.
+#[derive(Debug, Clone)]
+pub struct NodeData {
+    pub name: String,
+    /// The transformation relative to the parent node.
+    pub transform: Matrix4,
+    pub meshes: Vec<MeshHandle>,
+    /// Metadata entries in their original order.
+    pub metadata: Vec<(String, MetadataValueData)>,
+    pub children: Vec<NodeData>,
+}
+
+impl NodeData {
+    /// Deep copies `node` and its subtree.
+    pub fn from_node(node: &Node) -> NodeData {
+        // iterative, hostile files may nest deep enough to overflow the
+        // stack: copy the nodes in pre-order along with their parent's
+        // position, then move every node into its parent back to front
+        let mut flat: Vec<(Option<usize>, NodeData)> = Vec::new();
+        let mut stack = vec![(None, node.clone())];
+        while let Some((parent, node)) = stack.pop() {
+            let idx = flat.len();
+            flat.push((parent, NodeData {
+                name: node.name().unwrap_or("").to_owned(),
+                transform: node.transform(),
+                meshes: node.meshes().to_vec(),
+                metadata: node
+                    .meta_data()
+                    .map(|meta| meta.iter().map(|(k, v)| (k.to_owned(), v.into())).collect())
+                    .unwrap_or_default(),
+                children: Vec::new(),
+            }));
+            let first = stack.len();
+            stack.extend(node.children_iter().map(|child| (Some(idx), child)));
+            stack[first..].reverse();
+        }
+        while let Some((parent, mut data)) = flat.pop() {
+            // children were added last to first
+            data.children.reverse();
+            match parent {
+                Some(parent) => flat[parent].1.children.push(data),
+                None => return data,
+            }
+        }
+        unreachable!("the first node is the root")
+    }
+
+    /// This node or the first descendant (in depth-first order) with the
+    /// given name.
+    pub fn find(&self, name: &str) -> Option<&NodeData> {
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            if node.name == name {
+                return Some(node);
+            }
+            stack.extend(node.children.iter().rev());
+        }
+        None
+    }
+}
+
+// ++++++++++++++++++++ MeshData ++++++++++++++++++++
+
+/// Owned counterpart of `Bone`.
+#[derive(Debug, Clone)]
+pub struct BoneData {
+    pub name: String,
+    /// (vertex index, weight) pairs.
+    pub weights: Vec<(VertexIdx, f32)>,
+    pub offset_matrix: Matrix4,
+}
+
+impl BoneData {
+    pub fn from_bone(bone: &Bone) -> BoneData {
+        BoneData {
+            name: bone.name().to_owned(),
+            weights: bone.weights().iter().map(|w| (w.vertex_idx(), w.weight())).collect(),
+            offset_matrix: bone.offset_matrix(),
+        }
+    }
+}
+
+/// Owned counterpart of `Mesh`.
+///
+/// Vertex streams the mesh doesn't have are empty.
+#[derive(Debug, Clone)]
+pub struct MeshData {
+    pub name: String,
+    pub primitive_types: PrimitiveTypes,
+    pub vertices: Vec<Vector3>,
+    pub normals: Vec<Vector3>,
+    pub tangents: Vec<Vector3>,
+    pub bitangents: Vec<Vector3>,
+    /// Indexed by channel, always `MAX_COLOR_SETS` entries.
+    pub colors: Vec<Vec<Color4>>,
+    /// Indexed by channel, always `MAX_TEXTURE_COORDS` entries.
+    pub texture_coords: Vec<Vec<Vector3>>,
+    /// Indexed by channel, always `MAX_TEXTURE_COORDS` entries.
+    pub num_uv_components: Vec<usize>,
+    /// The vertex indices of each face.
+    pub faces: Vec<Vec<VertexIdx>>,
+    pub bones: Vec<BoneData>,
+    pub material: MaterialHandle,
+}
+
+impl MeshData {
+    pub fn from_mesh(mesh: &Mesh) -> MeshData {
+        MeshData {
+            name: mesh.name().unwrap_or("").to_owned(),
+            primitive_types: mesh.primitive_types(),
+            vertices: mesh.vertices().to_vec(),
+            normals: mesh.normals().to_vec(),
+            tangents: mesh.tangents().to_vec(),
+            bitangents: mesh.bitangents().to_vec(),
+            colors: (0..MAX_COLOR_SETS).map(|c| mesh.colors(c).to_vec()).collect(),
+            texture_coords: (0..MAX_TEXTURE_COORDS).map(|c| mesh.texture_coords(c).to_vec()).collect(),
+            num_uv_components: (0..MAX_TEXTURE_COORDS).map(|c| mesh.num_uv_components(c)).collect(),
+            faces: mesh.faces().iter().map(|f| f.indices().to_vec()).collect(),
+            bones: mesh.bones().iter().map(BoneData::from_bone).collect(),
+            material: mesh.material(),
+        }
+    }
+
+    /// Reorders the vertices so that vertices influenced by the same set
+    /// of bones are contiguous, for better cache behavior when skinning
+    /// and for dispatching skinning per bone subset.
+    ///
+    /// All vertex streams, faces and bone weights are remapped, and faces
+    /// are reordered to follow their vertices. Returns the resulting
+    /// chunks in vertex order, vertices without bones form the chunk with
+    /// an empty bone set.
+    pub fn sort_vertices_by_bone_locality(&mut self) -> Vec<BoneChunk> {
+        let len = self.vertices.len();
+        // bones are visited in order, so every set ends up sorted
+        let mut influences: Vec<Vec<usize>> = vec![Vec::new(); len];
+        for (bone_idx, bone) in self.bones.iter().enumerate() {
+            for &(vertex, weight) in &bone.weights {
+                if weight <= 0.0 {
+                    continue;
+                }
+                if let Some(set) = influences.get_mut(vertex as usize) {
+                    if set.last() != Some(&bone_idx) {
+                        set.push(bone_idx);
+                    }
+                }
+            }
+        }
+
+        let mut order: Vec<usize> = (0..len).collect();
+        order.sort_by(|&a, &b| influences[a].cmp(&influences[b]));
+        let mut remap = vec![0; len];
+        for (new, &old) in order.iter().enumerate() {
+            remap[old] = new as VertexIdx;
+        }
+
+        permute(&mut self.vertices, &order);
+        permute(&mut self.normals, &order);
+        permute(&mut self.tangents, &order);
+        permute(&mut self.bitangents, &order);
+        for channel in &mut self.colors {
+            permute(channel, &order);
+        }
+        for channel in &mut self.texture_coords {
+            permute(channel, &order);
+        }
+        for face in &mut self.faces {
+            for idx in face.iter_mut() {
+                if let Some(&new) = remap.get(*idx as usize) {
+                    *idx = new;
+                }
+            }
+        }
+        self.faces.sort_by_key(|face| face.iter().cloned().min());
+        for bone in &mut self.bones {
+            for weight in &mut bone.weights {
+                if let Some(&new) = remap.get(weight.0 as usize) {
+                    weight.0 = new;
+                }
+            }
+        }
+
+        let mut chunks: Vec<BoneChunk> = Vec::new();
+        for (new, &old) in order.iter().enumerate() {
+            match chunks.last_mut() {
+                Some(ref mut chunk) if chunk.bones == influences[old] => {
+                    chunk.vertices.end = new + 1;
+                    continue;
+                }
+                _ => {}
+            }
+            chunks.push(BoneChunk { bones: influences[old].clone(), vertices: new..new + 1 });
+        }
+        chunks
+    }
+}
+
+/// A range of vertices influenced by the same bones, see
+/// `MeshData::sort_vertices_by_bone_locality()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoneChunk {
+    /// Indices into `MeshData::bones`, sorted.
+    pub bones: Vec<usize>,
+    pub vertices: Range<usize>,
+}
+
+/// Reorders `stream` so that entry `i` is the former entry `order[i]`.
+/// Streams the mesh doesn't have (i.e. of another length) are left alone.
+fn permute<T: Copy>(stream: &mut Vec<T>, order: &[usize]) {
+    if stream.len() != order.len() {
+        return;
+    }
+    *stream = order.iter().map(|&i| stream[i]).collect();
+}
+
+// ++++++++++++++++++++ MaterialData ++++++++++++++++++++
+
+/// Owned counterpart of `Material`.
+#[derive(Debug, Clone)]
+pub struct MaterialData {
+    pub properties: MaterialProperties,
+    /// The texture stack of every texture type the material uses.
+    pub textures: Vec<(TextureType, Vec<TextureProperties>)>,
+}
+
+impl MaterialData {
+    pub fn from_material(material: &Material) -> MaterialData {
+        let textures = (0x1..0xD)
+            .map(|ty| unsafe { TextureType::from_ffi(ty) })
+            .map(|tex_ty| {
+                let stack: Vec<TextureProperties> = (0..material.count_texture_properties(tex_ty))
+                    .filter_map(|idx| material.texture_properties(tex_ty, idx))
+                    .collect();
+                (tex_ty, stack)
+            })
+            .filter(|&(_, ref stack)| !stack.is_empty())
+            .collect();
+        MaterialData { properties: material.material_properties(), textures }
+    }
+
+    /// The texture stack for `tex_ty`, empty if the material has none.
+    pub fn textures(&self, tex_ty: TextureType) -> &[TextureProperties] {
+        self.textures.iter().find(|&&(ty, _)| ty == tex_ty).map_or(&[], |&(_, ref stack)| &stack[..])
+    }
+}
+
+// ++++++++++++++++++++ TextureData ++++++++++++++++++++
+
+/// Owned counterpart of `Texture`.
+#[derive(Debug, Clone)]
+pub struct TextureData {
+    /// Width in texels, or the size of `data` for compressed textures.
+    pub width: usize,
+    /// Height in texels, 0 for compressed textures.
+    pub height: usize,
+    /// See `Texture::format_hint()`.
+    pub format_hint: Option<String>,
+    /// The file contents for compressed textures, otherwise `width *
+    /// height` texels of 4 bytes each in b, g, r, a order.
+    pub data: Vec<u8>,
+}
+
+impl TextureData {
+    pub fn from_texture(texture: &Texture) -> TextureData {
+        TextureData {
+            width: texture.raw().mWidth as usize,
+            height: texture.raw().mHeight as usize,
+            format_hint: texture.format_hint().map(|hint| hint.to_owned()),
+            data: texture.as_bytes().to_vec(),
+        }
+    }
+
+    /// Whether `data` holds an image file rather than texels.
+    pub fn is_compressed(&self) -> bool {
+        self.height == 0
+    }
+
+    /// The full mip chain of this texture, starting with a copy of it and
+    /// ending with the 1x1 level.
+    ///
+    /// Each level has half the size of the previous one (rounded down, at
+    /// least 1). With `ColorSpace::Srgb`, colors are averaged in linear
+    /// space so the levels don't darken; alpha is always linear. Fails for
+    /// compressed textures, which would have to be decoded first.
+    pub fn generate_mipmaps(&self, filter: MipFilter, color_space: ColorSpace) -> Result<Vec<TextureData>, String> {
+        if self.is_compressed() {
+            return Err("can't generate mipmaps for a compressed texture".to_owned());
+        }
+        if self.data.len() != self.width * self.height * 4 {
+            return Err(format!("texture data doesn't match its size of {}x{}", self.width, self.height));
+        }
+        let mut ret = vec![self.clone()];
+        loop {
+            let level = {
+                let last = &ret[ret.len() - 1];
+                if last.width == 1 && last.height == 1 {
+                    break;
+                }
+                last.downsample(filter, color_space)
+            };
+            ret.push(level);
+        }
+        Ok(ret)
+    }
+
+    fn downsample(&self, filter: MipFilter, color_space: ColorSpace) -> TextureData {
+        let (width, height) = ((self.width / 2).max(1), (self.height / 2).max(1));
+        let texel = |x: usize, y: usize| {
+            let i = (y.min(self.height - 1) * self.width + x.min(self.width - 1)) * 4;
+            &self.data[i..i + 4]
+        };
+        let mut data = Vec::with_capacity(width * height * 4);
+        for y in 0..height {
+            for x in 0..width {
+                match filter {
+                    MipFilter::Nearest => data.extend_from_slice(texel(x * 2, y * 2)),
+                    MipFilter::Box => {
+                        let (xs, ys) = (mip_taps(self.width, width, x), mip_taps(self.height, height, y));
+                        for c in 0..4 {
+                            // b, g, r are colors, a is linear
+                            let srgb = c < 3 && color_space == ColorSpace::Srgb;
+                            let mut avg = 0.0;
+                            for &(sy, wy) in &ys {
+                                for &(sx, wx) in &xs {
+                                    let v = texel(sx, sy)[c] as f32 / 255.0;
+                                    avg += if srgb { srgb_to_linear(v) } else { v } * wx * wy;
+                                }
+                            }
+                            let v = if srgb { linear_to_srgb(avg) } else { avg };
+                            data.push((v * 255.0 + 0.5).clamp(0.0, 255.0) as u8);
+                        }
+                    }
+                }
+            }
+        }
+        TextureData { width, height, format_hint: self.format_hint.clone(), data }
+    }
+}
+
+/// The source texels along one axis covered by texel `i` of a level with
+/// `dst` texels, with weights by overlap summing to 1.
+///
+/// For even sizes these are two texels of equal weight. For odd sizes
+/// each destination texel spans more than two source texels, so three
+/// are used and the one shared with the neighbour is weighted by the part
+/// that falls into this texel (e.g. 0.4, 0.4, 0.2 from 5 to 2 texels).
+fn mip_taps(src: usize, dst: usize, i: usize) -> Vec<(usize, f32)> {
+    let scale = src as f32 / dst as f32;
+    let (start, end) = (i as f32 * scale, (i + 1) as f32 * scale);
+    (start.floor() as usize..(end.ceil() as usize).min(src))
+        .map(|j| {
+            let overlap = end.min(j as f32 + 1.0) - start.max(j as f32);
+            (j, overlap / scale)
+        })
+        .filter(|&(_, weight)| weight > 0.0)
+        .collect()
+}
+
+/// How `TextureData::generate_mipmaps()` computes a texel from the
+/// texels of the previous level it covers (2x2, or up to 3x3 at odd sizes).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MipFilter {
+    /// Takes the top-left texel, e.g. for index or ID maps.
+    Nearest,
+    /// Averages the covered texels, weighted by how much of each falls
+    /// into the new texel.
+    Box,
+}
+
+/// How the color channels of a texture are encoded, see
+/// `TextureData::generate_mipmaps()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// Colors, e.g. diffuse or emissive maps.
+    Srgb,
+    /// Data, e.g. normal, roughness or height maps.
+    Linear,
+}
+
+fn srgb_to_linear(v: f32) -> f32 {
+    if v <= 0.04045 { v / 12.92 } else { ((v + 0.055) / 1.055).powf(2.4) }
+}
+
+fn linear_to_srgb(v: f32) -> f32 {
+    if v <= 0.0031308 { v * 12.92 } else { 1.055 * v.powf(1.0 / 2.4) - 0.055 }
+}
+
+// ++++++++++++++++++++ AnimationData ++++++++++++++++++++
+
+/// Owned counterpart of `NodeAnim`. Keys are (time in ticks, value) pairs.
+#[derive(Debug, Clone)]
+pub struct NodeAnimData {
+    pub node_name: String,
+    pub position_keys: Vec<(f64, Vector3)>,
+    pub rotation_keys: Vec<(f64, Quaternion)>,
+    pub scaling_keys: Vec<(f64, Vector3)>,
+    pub pre_state: AnimBehavior,
+    pub post_state: AnimBehavior,
+}
+
+impl NodeAnimData {
+    pub fn from_node_anim(channel: &NodeAnim) -> NodeAnimData {
+        NodeAnimData {
+            node_name: channel.node_name().to_owned(),
+            position_keys: channel.position_keys().iter().map(|k| (k.time(), k.value())).collect(),
+            rotation_keys: channel.rotation_keys().iter().map(|k| (k.time(), k.value())).collect(),
+            scaling_keys: channel.scaling_keys().iter().map(|k| (k.time(), k.value())).collect(),
+            pre_state: channel.pre_state(),
+            post_state: channel.post_state(),
+        }
+    }
+}
+
+/// Owned counterpart of `Animation`.
+#[derive(Debug, Clone)]
+pub struct AnimationData {
+    pub name: String,
+    /// Duration in ticks.
+    pub duration: f64,
+    /// 0 if not specified in the imported file.
+    pub ticks_per_second: f64,
+    pub channels: Vec<NodeAnimData>,
+}
+
+impl AnimationData {
+    pub fn from_animation(animation: &Animation) -> AnimationData {
+        AnimationData {
+            name: animation.name().unwrap_or("").to_owned(),
+            duration: animation.duration(),
+            ticks_per_second: animation.ticks_per_second(),
+            channels: animation.channels().iter().map(NodeAnimData::from_node_anim).collect(),
+        }
+    }
+}
+
+// ++++++++++++++++++++ LightData / CameraData ++++++++++++++++++++
+
+/// Owned counterpart of `Light`.
+#[derive(Debug, Clone)]
+pub struct LightData {
+    pub name: String,
+    pub source_type: LightSourceType,
+    pub position: Vector3,
+    pub direction: Vector3,
+    pub up: Vector3,
+    pub attenuation_constant: f32,
+    pub attenuation_linear: f32,
+    pub attenuation_quadratic: f32,
+    pub color_diffuse: Color3,
+    pub color_specular: Color3,
+    pub color_ambient: Color3,
+    pub angle_inner_cone: f32,
+    pub angle_outer_cone: f32,
+    pub size: Vector2,
+}
+
+impl LightData {
+    pub fn from_light(light: &Light) -> LightData {
+        LightData {
+            name: light.name().to_owned(),
+            source_type: light.source_type(),
+            position: light.position(),
+            direction: light.direction(),
+            up: light.up(),
+            attenuation_constant: light.attenuation_constant(),
+            attenuation_linear: light.attenuation_linear(),
+            attenuation_quadratic: light.attenuation_quadratic(),
+            color_diffuse: light.color_diffuse(),
+            color_specular: light.color_specular(),
+            color_ambient: light.color_ambient(),
+            angle_inner_cone: light.angle_inner_cone(),
+            angle_outer_cone: light.angle_outer_cone(),
+            size: light.size(),
+        }
+    }
+}
+
+/// Owned counterpart of `Camera`.
+#[derive(Debug, Clone)]
+pub struct CameraData {
+    pub name: String,
+    pub position: Vector3,
+    pub up: Vector3,
+    pub look_at: Vector3,
+    pub horizontal_fov: f32,
+    pub clip_plane_near: f32,
+    pub clip_plane_far: f32,
+    pub aspect: f32,
+}
+
+impl CameraData {
+    pub fn from_camera(camera: &Camera) -> CameraData {
+        CameraData {
+            name: camera.name().to_owned(),
+            position: camera.position(),
+            up: camera.up(),
+            look_at: camera.look_at(),
+            horizontal_fov: camera.horizontal_fov(),
+            clip_plane_near: camera.clip_plane_near(),
+            clip_plane_far: camera.clip_plane_far(),
+            aspect: camera.aspect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mesh::TRIANGLE;
+
+    #[test]
+    fn mip_taps_even() {
+        assert_eq!(mip_taps(4, 2, 0), vec![(0, 0.5), (1, 0.5)]);
+        assert_eq!(mip_taps(4, 2, 1), vec![(2, 0.5), (3, 0.5)]);
+    }
+
+    #[test]
+    fn mip_taps_odd() {
+        let taps = mip_taps(5, 2, 0);
+        let idx: Vec<usize> = taps.iter().map(|&(j, _)| j).collect();
+        assert_eq!(idx, vec![0, 1, 2]);
+        assert!((taps[0].1 - 0.4).abs() < 1e-6);
+        assert!((taps[1].1 - 0.4).abs() < 1e-6);
+        assert!((taps[2].1 - 0.2).abs() < 1e-6);
+        for i in 0..2 {
+            let sum: f32 = mip_taps(5, 2, i).iter().map(|&(_, w)| w).sum();
+            assert!((sum - 1.0).abs() < 1e-6);
+        }
+        assert_eq!(mip_taps(1, 1, 0), vec![(0, 1.0)]);
+    }
+
+    fn texture(width: usize, height: usize, texels: &[[u8; 4]]) -> TextureData {
+        TextureData { width, height, format_hint: None, data: texels.iter().flat_map(|t| t.iter().cloned()).collect() }
+    }
+
+    #[test]
+    fn generate_mipmaps_odd_size() {
+        // a white column in a 3x1 texture, the middle texel is shared by
+        // the single texel of the next level
+        let tex = texture(3, 1, &[[0, 0, 0, 255], [255, 255, 255, 255], [0, 0, 0, 255]]);
+        let mips = tex.generate_mipmaps(MipFilter::Box, ColorSpace::Linear).unwrap();
+        assert_eq!(mips.len(), 2);
+        assert_eq!((mips[1].width, mips[1].height), (1, 1));
+        assert_eq!(mips[1].data, vec![85, 85, 85, 255]);
+
+        let tex = texture(5, 5, &[[200, 100, 50, 255]; 25]);
+        let mips = tex.generate_mipmaps(MipFilter::Box, ColorSpace::Srgb).unwrap();
+        let sizes: Vec<_> = mips.iter().map(|m| (m.width, m.height)).collect();
+        assert_eq!(sizes, vec![(5, 5), (2, 2), (1, 1)]);
+        // a uniform texture stays uniform
+        for level in &mips {
+            for texel in level.data.chunks(4) {
+                assert_eq!(texel, &[200, 100, 50, 255]);
+            }
+        }
+    }
+
+    #[test]
+    fn generate_mipmaps_rejects_invalid() {
+        let compressed = TextureData { width: 16, height: 0, format_hint: Some("png".to_owned()), data: vec![0; 16] };
+        assert!(compressed.generate_mipmaps(MipFilter::Box, ColorSpace::Srgb).is_err());
+        let short = TextureData { width: 2, height: 2, format_hint: None, data: vec![0; 4] };
+        assert!(short.generate_mipmaps(MipFilter::Box, ColorSpace::Srgb).is_err());
+    }
+
+    fn bone(name: &str, weights: Vec<(VertexIdx, f32)>) -> BoneData {
+        BoneData { name: name.to_owned(), weights, offset_matrix: ::prim::IDENTITY }
+    }
+
+    #[test]
+    fn sort_vertices_by_bone_locality() {
+        let mut mesh = MeshData {
+            name: String::new(),
+            primitive_types: TRIANGLE,
+            vertices: (0..6).map(|i| [i as f32, 0.0, 0.0]).collect(),
+            normals: Vec::new(),
+            tangents: Vec::new(),
+            bitangents: Vec::new(),
+            colors: vec![Vec::new(); MAX_COLOR_SETS],
+            texture_coords: vec![Vec::new(); MAX_TEXTURE_COORDS],
+            num_uv_components: vec![0; MAX_TEXTURE_COORDS],
+            faces: vec![vec![0, 1, 2], vec![3, 4, 5]],
+            // vertex 2 and 5 have no bones, 0 and 3 bone 1, 1 and 4 both
+            bones: vec![bone("a", vec![(1, 0.5), (4, 0.5)]), bone("b", vec![(0, 1.0), (1, 0.5), (3, 1.0), (4, 0.5)])],
+            material: MaterialHandle(0),
+        };
+        let chunks = mesh.sort_vertices_by_bone_locality();
+        assert_eq!(
+            chunks,
+            vec![
+                BoneChunk { bones: vec![], vertices: 0..2 },
+                BoneChunk { bones: vec![0, 1], vertices: 2..4 },
+                BoneChunk { bones: vec![1], vertices: 4..6 },
+            ]
+        );
+        let xs: Vec<f32> = mesh.vertices.iter().map(|v| v[0]).collect();
+        assert_eq!(xs, vec![2.0, 5.0, 1.0, 4.0, 0.0, 3.0]);
+        // faces still reference the same positions
+        for face in &mesh.faces {
+            let mut xs: Vec<f32> = face.iter().map(|&i| mesh.vertices[i as usize][0]).collect();
+            xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            assert!(xs == vec![0.0, 1.0, 2.0] || xs == vec![3.0, 4.0, 5.0]);
+        }
+        assert_eq!(mesh.bones[0].weights, vec![(2, 0.5), (3, 0.5)]);
+        assert_eq!(mesh.bones[1].weights, vec![(4, 1.0), (2, 0.5), (5, 1.0), (3, 0.5)]);
+    }
+}
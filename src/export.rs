@@ -0,0 +1,105 @@
+//! Writing scenes back out, see `export()`.
+
+use postprocess::PostProcessSteps;
+use scene::Scene;
+use prim;
+use ffi;
+use std::ffi::{CStr, CString};
+use std::path::Path;
+use std::slice;
+use libc::{c_char, c_uint};
+
+/// A file format assimp can export to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportFormat {
+    /// Short identifier passed to `export()`, e.g. `"obj"` or `"gltf2"`.
+    pub id: String,
+    /// Human readable description, e.g. `"Wavefront OBJ format"`.
+    pub description: String,
+    /// The recommended file extension, without leading dot.
+    pub file_extension: String,
+}
+
+impl ExportFormat {
+    /// All exporters compiled into the linked assimp library.
+    pub fn all() -> Vec<ExportFormat> {
+        let mut ret = Vec::new();
+        unsafe {
+            for idx in 0..ffi::aiGetExportFormatCount() {
+                let desc = ffi::aiGetExportFormatDescription(idx);
+                if desc.is_null() {
+                    continue;
+                }
+                ret.push(ExportFormat {
+                    id: string((*desc).id),
+                    description: string((*desc).description),
+                    file_extension: string((*desc).fileExtension),
+                });
+                ffi::aiReleaseExportFormatDescription(desc);
+            }
+        }
+        ret
+    }
+}
+
+/// A single file produced by `export_to_blobs()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportBlob {
+    /// Empty for the main file. Additional files (e.g. the `.mtl` of an
+    /// OBJ export) are named by assimp, usually after their extension.
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+/// Exports `scene` to the file at `path` in the format `format_id` (see
+/// `ExportFormat::all()`).
+///
+/// `steps` are applied to a copy of the scene before exporting, the scene
+/// itself is left untouched. Formats which consist of several files write
+/// them next to `path`.
+pub fn export<P: AsRef<Path>>(scene: &Scene, format_id: &str, path: P, steps: PostProcessSteps) -> Result<(), String> {
+    let id = CString::new(format_id).map_err(|e| e.to_string())?;
+    let file = prim::path_to_cstring(path.as_ref())?;
+    let ret = unsafe { ffi::aiExportScene(scene.raw(), id.as_ptr(), file.as_ptr(), steps.bits() as c_uint) };
+    match ret {
+        ffi::aiReturn::aiReturn_SUCCESS => Ok(()),
+        _ => Err(format!("failed to export {} as '{}'", path.as_ref().display(), format_id)),
+    }
+}
+
+/// Exports `scene` to memory in the format `format_id` (see
+/// `ExportFormat::all()`).
+///
+/// The main file comes first, followed by any additional files the format
+/// consists of. See `export()` for `steps`.
+pub fn export_to_blobs(scene: &Scene, format_id: &str, steps: PostProcessSteps) -> Result<Vec<ExportBlob>, String> {
+    let id = CString::new(format_id).map_err(|e| e.to_string())?;
+    unsafe {
+        let first = ffi::aiExportSceneToBlob(scene.raw(), id.as_ptr(), steps.bits() as c_uint);
+        if first.is_null() {
+            return Err(format!("failed to export as '{}'", format_id));
+        }
+        let mut ret = Vec::new();
+        let mut blob = first;
+        while !blob.is_null() {
+            let data = if (*blob).data.is_null() {
+                Vec::new()
+            } else {
+                slice::from_raw_parts((*blob).data as *const u8, (*blob).size).to_vec()
+            };
+            let name = prim::str(&(*blob).name).unwrap_or("").to_owned();
+            ret.push(ExportBlob { name, data });
+            blob = (*blob).next;
+        }
+        // releases the whole chain
+        ffi::aiReleaseExportBlob(first);
+        Ok(ret)
+    }
+}
+
+fn string(ptr: *const c_char) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+    unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned()
+}
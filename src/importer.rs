@@ -0,0 +1,233 @@
+use prim;
+use ffi;
+use std::ffi::{CStr, CString};
+use libc::{c_char, c_uint};
+
+bitflags!{
+    /// Mixed set of flags for `ImporterDesc`, indicating some features
+    /// common to many importers.
+    pub flags ImporterFlags: c_uint {
+        /// Indicates that there is a textual encoding of the
+        /// file format; and that it is supported.
+        const SUPPORT_TEXT_FLAVOUR = 0x1,
+
+        /// Indicates that there is a binary encoding of the
+        /// file format; and that it is supported.
+        const SUPPORT_BINARY_FLAVOUR = 0x2,
+
+        /// Indicates that there is a compressed encoding of the
+        /// file format; and that it is supported.
+        const SUPPORT_COMPRESSED_FLAVOUR = 0x4,
+
+        /// Indicates that the importer reads only a very particular
+        /// subset of the file format.
+        ///
+        /// This happens commonly for declarative or procedural formats
+        /// which cannot easily be mapped to #aiScene
+        const LIMITED_SUPPORT = 0x8,
+
+        /// Indicates that the importer is highly experimental and
+        /// should be used with care.
+        ///
+        /// This only happens for trunk (i.e. SVN) versions, experimental
+        /// code is not included in releases.
+        const EXPERIMENTAL = 0x10,
+    }
+}
+
+/// Meta information about a particular importer.
+///
+/// Importers need to fill this structure, but they can freely decide how
+/// talkative they are. A common use case for loader meta info is a user
+/// interface in which the user can choose between various import/export
+/// file formats. Building such an UI by hand means a lot of maintenance
+/// as importers/exporters are added to Assimp, so it might be useful
+/// to have a common mechanism to query some rough importer
+/// characteristics.
+#[derive(Clone, Copy)]
+pub struct ImporterDesc {
+    raw: &'static ffi::aiImporterDesc,
+}
+
+impl ImporterDesc {
+    #[doc(hidden)]
+    pub unsafe fn from_ptr(ptr: *const ffi::aiImporterDesc) -> Self {
+        assert!(!ptr.is_null());
+        ImporterDesc { raw: &*ptr }
+    }
+
+    /// All importers compiled into the linked assimp library.
+    pub fn all() -> Vec<ImporterDesc> {
+        unsafe {
+            (0..ffi::aiGetImportFormatCount())
+                .map(|idx| ffi::aiGetImportFormatDescription(idx))
+                .filter(|ptr| !ptr.is_null())
+                .map(|ptr| Self::from_ptr(ptr))
+                .collect()
+        }
+    }
+
+    /// The importer responsible for the given file extension (without
+    /// leading dot), if any.
+    pub fn for_extension(ext: &str) -> Option<ImporterDesc> {
+        let ext = CString::new(ext).ok()?;
+        unsafe {
+            let ptr = ffi::aiGetImporterDesc(ext.as_ptr());
+            if ptr.is_null() {
+                return None;
+            }
+            Some(Self::from_ptr(ptr))
+        }
+    }
+
+    /// Full name of the importer (i.e. Blender3D importer)
+    pub fn name(&self) -> &'static str {
+        cstr(self.raw.mName).unwrap_or("")
+    }
+
+    /// Original author (left blank if unknown or whole assimp team)
+    pub fn author(&self) -> Option<&'static str> {
+        cstr(self.raw.mAuthor)
+    }
+
+    /// Current maintainer, left blank if the author maintains
+    pub fn maintainer(&self) -> Option<&'static str> {
+        cstr(self.raw.mMaintainer)
+    }
+
+    /// Implementation comments, i.e. unimplemented features
+    pub fn comments(&self) -> Option<&'static str> {
+        cstr(self.raw.mComments)
+    }
+
+    /// These flags indicate some characteristics common to many
+    /// importers.
+    pub fn flags(&self) -> ImporterFlags {
+        ImporterFlags::from_bits_truncate(self.raw.mFlags)
+    }
+
+    /// Minimum format version that can be loaded im major.minor format,
+    /// both are set to 0 if there is either no version scheme
+    /// or if the loader doesn't care.
+    pub fn min_version(&self) -> (u32, u32) {
+        (self.raw.mMinMajor, self.raw.mMinMinor)
+    }
+
+    /// Maximum format version that can be loaded im major.minor format,
+    /// both are set to 0 if there is either no version scheme
+    /// or if the loader doesn't care. Loaders that expect to be
+    /// forward-compatible to potential future format versions should
+    /// indicate zero, otherwise they should specify the current
+    /// maximum version.
+    pub fn max_version(&self) -> (u32, u32) {
+        (self.raw.mMaxMajor, self.raw.mMaxMinor)
+    }
+
+    /// Known ways in which importing with this importer loses information
+    /// of the source file.
+    ///
+    /// Combines the importer's flags and implementation comments with a
+    /// curated list of well-known limitations of assimp's importers, so
+    /// tools can warn before importing. The list is not exhaustive.
+    pub fn limitations(&self) -> Vec<&'static str> {
+        let mut ret = Vec::new();
+        let flags = self.flags();
+        if flags.contains(LIMITED_SUPPORT) {
+            ret.push("only a subset of the file format is supported");
+        }
+        if flags.contains(EXPERIMENTAL) {
+            ret.push("the importer is experimental");
+        }
+        for ext in self.file_extensions() {
+            for &(known, limitations) in KNOWN_LIMITATIONS {
+                if !ext.eq_ignore_ascii_case(known) {
+                    continue;
+                }
+                for limitation in limitations {
+                    if !ret.contains(limitation) {
+                        ret.push(limitation);
+                    }
+                }
+            }
+        }
+        if let Some(comments) = self.comments() {
+            ret.push(comments);
+        }
+        ret
+    }
+
+    /// List of file extensions this importer can handle, without
+    /// leading dots.
+    ///
+    /// This is only a hint, assimp will also detect files by their
+    /// content.
+    pub fn file_extensions(&self) -> Vec<&'static str> {
+        cstr(self.raw.mFileExtensions)
+            .map(|exts| exts.split_whitespace().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// The file extensions (lowercase, without leading dot) of all importers
+/// in the linked assimp library, sorted and without duplicates.
+pub fn supported_extensions() -> Vec<String> {
+    let mut list = ffi::aiString::default();
+    unsafe { ffi::aiGetExtensionList(&mut list) };
+    // formatted as "*.3ds;*.obj;..."
+    let mut ret: Vec<String> = prim::str(&list)
+        .unwrap_or("")
+        .split(';')
+        .map(|ext| ext.trim().trim_start_matches('*').trim_start_matches('.').to_lowercase())
+        .filter(|ext| !ext.is_empty())
+        .collect();
+    ret.sort();
+    ret.dedup();
+    ret
+}
+
+/// Whether the linked assimp library can import files with the given
+/// extension. Case-insensitive, a leading `.` or `*.` is optional.
+pub fn is_extension_supported(ext: &str) -> bool {
+    // assimp expects the leading dot
+    let ext = format!(".{}", ext.trim_start_matches('*').trim_start_matches('.'));
+    let ext = match CString::new(ext) {
+        Ok(ext) => ext,
+        Err(_) => return false,
+    };
+    unsafe { ffi::aiIsExtensionSupported(ext.as_ptr()) != 0 }
+}
+
+/// A filter description for file dialogs covering all supported formats,
+/// e.g. `3D Models (*.3ds *.blend *.fbx ...)`.
+pub fn file_dialog_filter() -> String {
+    let patterns: Vec<String> = supported_extensions().iter().map(|ext| format!("*.{}", ext)).collect();
+    format!("3D Models ({})", patterns.join(" "))
+}
+
+/// Limitations of assimp's importers by file extension, see
+/// `ImporterDesc::limitations()`.
+const KNOWN_LIMITATIONS: &'static [(&'static str, &'static [&'static str])] = &[
+    ("3ds", &["no skeletal animation", "only keyframed node animation", "procedural textures are dropped"]),
+    ("blend", &["modifiers other than mirror and subdivision are ignored", "no animation", "node-based materials are dropped"]),
+    ("dae", &["physics and constraints are dropped", "controllers other than skin and morph are dropped"]),
+    ("fbx", &["NURBS and patches are tessellated or dropped", "constraints are dropped", "blend shapes are dropped"]),
+    ("ifc", &["only geometry and the spatial hierarchy are imported", "BIM semantics and properties are dropped"]),
+    ("lwo", &["procedural textures are dropped", "envelopes are not imported"]),
+    ("lws", &["only the scene layout is imported, objects are loaded from separate files"]),
+    ("md5mesh", &["animations are stored in separate .md5anim files"]),
+    ("obj", &["no animation", "no skinning", "free-form curves and surfaces are dropped"]),
+    ("ply", &["no animation", "materials are limited to vertex colors"]),
+    ("stl", &["geometry only, no materials, textures or hierarchy"]),
+    ("x", &["compressed (tzip/bzip) files are not supported"]),
+];
+
+fn cstr(ptr: *const c_char) -> Option<&'static str> {
+    if ptr.is_null() {
+        return None;
+    }
+    let s = unsafe { CStr::from_ptr(ptr) }.to_str().ok()?;
+    if s.is_empty() {
+        return None;
+    }
+    Some(s)
+}
@@ -0,0 +1,114 @@
+use mesh::Mesh;
+use prim::{self, Matrix4, Vector3};
+
+/// How the capture directions of an `ImposterRig` are distributed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImposterLayout {
+    /// `n` x `n` views covering the full sphere, laid out by octahedral
+    /// mapping.
+    Octahedral(u32),
+    /// `n` x `n` views covering the upper hemisphere (+Y), laid out by
+    /// hemi-octahedral mapping. Suited for objects only seen from above.
+    HemiOctahedral(u32),
+}
+
+/// A single capture view of an `ImposterRig`.
+#[derive(Debug, Clone, Copy)]
+pub struct ImposterView {
+    /// Cell of the view in the imposter atlas, x and y.
+    pub cell: [u32; 2],
+    /// Unit vector from the object's center towards the camera.
+    pub direction: Vector3,
+    /// Camera position.
+    pub eye: Vector3,
+    /// World to camera transformation. The camera looks along -Z with +Y
+    /// up, as with OpenGL.
+    pub view: Matrix4,
+}
+
+/// Camera poses for capturing imposter (billboard) textures of an object.
+///
+/// All views use an orthographic projection with a half extent of `radius`
+/// in x and y and the clip planes `near` and `far`, so the object's
+/// bounding sphere exactly fills every view.
+#[derive(Debug, Clone)]
+pub struct ImposterRig {
+    /// Center of the object's bounding sphere.
+    pub center: Vector3,
+    /// Radius of the object's bounding sphere.
+    pub radius: f32,
+    pub near: f32,
+    pub far: f32,
+    pub views: Vec<ImposterView>,
+}
+
+impl ImposterRig {
+    /// Builds the capture rig for a mesh in its local space, `None` if the
+    /// mesh has no vertices.
+    pub fn for_mesh(mesh: &Mesh, layout: ImposterLayout) -> Option<ImposterRig> {
+        let aabb = mesh.aabb()?;
+        let center = aabb.center();
+        let radius = (prim::length(aabb.size()) * 0.5).max(1e-6);
+        // keep the object between the clip planes with some margin
+        let distance = radius * 2.0;
+
+        let (n, hemi) = match layout {
+            ImposterLayout::Octahedral(n) => (n, false),
+            ImposterLayout::HemiOctahedral(n) => (n, true),
+        };
+        let mut views = Vec::with_capacity((n * n) as usize);
+        for y in 0..n {
+            for x in 0..n {
+                let u = (x as f32 + 0.5) / n as f32 * 2.0 - 1.0;
+                let v = (y as f32 + 0.5) / n as f32 * 2.0 - 1.0;
+                let direction = if hemi { hemi_octahedral(u, v) } else { octahedral(u, v) };
+                let eye = [
+                    center[0] + direction[0] * distance,
+                    center[1] + direction[1] * distance,
+                    center[2] + direction[2] * distance,
+                ];
+                views.push(ImposterView { cell: [x, y], direction, eye, view: look_at(eye, direction) });
+            }
+        }
+
+        Some(ImposterRig { center, radius, near: distance - radius, far: distance + radius, views })
+    }
+}
+
+/// Decodes a point of the [-1, 1]² square to a direction on the sphere.
+fn octahedral(u: f32, v: f32) -> Vector3 {
+    let y = 1.0 - u.abs() - v.abs();
+    let (x, z) = if y < 0.0 {
+        ((1.0 - v.abs()) * u.signum(), (1.0 - u.abs()) * v.signum())
+    } else {
+        (u, v)
+    };
+    normalize([x, y, z])
+}
+
+/// Decodes a point of the [-1, 1]² square to a direction on the upper
+/// hemisphere.
+fn hemi_octahedral(u: f32, v: f32) -> Vector3 {
+    let (x, z) = ((u + v) * 0.5, (u - v) * 0.5);
+    normalize([x, 1.0 - x.abs() - z.abs(), z])
+}
+
+fn normalize(v: Vector3) -> Vector3 {
+    let len = prim::length(v);
+    [v[0] / len, v[1] / len, v[2] / len]
+}
+
+/// View matrix of a camera at `eye` looking along `-direction`.
+fn look_at(eye: Vector3, direction: Vector3) -> Matrix4 {
+    // camera z points away from the target
+    let z = direction;
+    let up = if z[1].abs() > 0.999 { [0.0, 0.0, -z[1].signum()] } else { [0.0, 1.0, 0.0] };
+    let x = normalize(prim::cross(up, z));
+    let y = prim::cross(z, x);
+    [
+        [x[0], x[1], x[2], -prim::dot(x, eye)],
+        [y[0], y[1], y[2], -prim::dot(y, eye)],
+        [z[0], z[1], z[2], -prim::dot(z, eye)],
+        [0.0, 0.0, 0.0, 1.0],
+    ]
+}
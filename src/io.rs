@@ -0,0 +1,160 @@
+//! Custom file systems for the importer, see `Scene::from_file_with_io()`.
+//!
+//! By default assimp reads files (and the files they reference, e.g.
+//! material libraries or external textures) from disk. Implementing
+//! `FileSystem` redirects all of these reads, e.g. to an archive or an
+//! asset database.
+
+use ffi;
+use std::ffi::CStr;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use libc::{c_char, size_t};
+
+/// A readable and seekable stream, as returned by `FileSystem::open()`.
+pub trait ReadSeek: Read + Seek {}
+
+impl<T: Read + Seek> ReadSeek for T {}
+
+/// The file system assimp reads from.
+///
+/// Paths are passed through as assimp builds them: the path given to the
+/// import call, and paths of referenced files resolved relative to it.
+/// Files are only ever opened for reading.
+pub trait FileSystem {
+    /// Opens the file at `path` for reading.
+    fn open(&mut self, path: &str) -> io::Result<Box<dyn ReadSeek>>;
+}
+
+/// Builds the `aiFileIO` forwarding to `fs`.
+///
+/// The returned struct points to `fs`, which must outlive the import call.
+#[doc(hidden)]
+pub fn file_io(fs: &mut &mut dyn FileSystem) -> ffi::aiFileIO {
+    ffi::aiFileIO {
+        OpenProc: Some(open_proc),
+        CloseProc: Some(close_proc),
+        UserData: fs as *mut &mut dyn FileSystem as *mut c_char,
+    }
+}
+
+/// Runs `f`, returning `default` if it panics. Unwinding into assimp is
+/// undefined behaviour.
+fn guard<T, F: FnOnce() -> T>(default: T, f: F) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(default)
+}
+
+unsafe fn stream<'a>(file: *mut ffi::aiFile) -> &'a mut Box<dyn ReadSeek> {
+    &mut *((*file).UserData as *mut Box<dyn ReadSeek>)
+}
+
+unsafe extern "C" fn open_proc(
+    io: *mut ffi::aiFileIO,
+    path: *const c_char,
+    mode: *const c_char,
+) -> *mut ffi::aiFile {
+    let mode = CStr::from_ptr(mode).to_bytes();
+    if mode.iter().any(|c| b"wa+".contains(c)) {
+        return ptr::null_mut();
+    }
+    let fs = &mut *((*io).UserData as *mut &mut dyn FileSystem);
+    let path = CStr::from_ptr(path).to_string_lossy();
+    let stream = match guard(None, || fs.open(&path).ok()) {
+        Some(stream) => stream,
+        None => return ptr::null_mut(),
+    };
+    Box::into_raw(Box::new(ffi::aiFile {
+        ReadProc: Some(read_proc),
+        WriteProc: Some(write_proc),
+        TellProc: Some(tell_proc),
+        FileSizeProc: Some(size_proc),
+        SeekProc: Some(seek_proc),
+        FlushProc: Some(flush_proc),
+        UserData: Box::into_raw(Box::new(stream)) as *mut c_char,
+    }))
+}
+
+unsafe extern "C" fn close_proc(_io: *mut ffi::aiFileIO, file: *mut ffi::aiFile) {
+    if file.is_null() {
+        return;
+    }
+    let file = Box::from_raw(file);
+    let stream = Box::from_raw(file.UserData as *mut Box<dyn ReadSeek>);
+    guard((), move || drop(stream));
+}
+
+/// Reads `count` elements of `size` bytes, returns the number of complete
+/// elements read (like `fread`).
+unsafe extern "C" fn read_proc(
+    file: *mut ffi::aiFile,
+    buf: *mut c_char,
+    size: size_t,
+    count: size_t,
+) -> size_t {
+    let len = match size.checked_mul(count) {
+        Some(len) if len > 0 => len,
+        _ => return 0,
+    };
+    let buf = ::std::slice::from_raw_parts_mut(buf as *mut u8, len);
+    let stream = stream(file);
+    guard(0, || {
+        let mut read = 0;
+        while read < len {
+            match stream.read(&mut buf[read..]) {
+                Ok(0) => break,
+                Ok(n) => read += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(_) => break,
+            }
+        }
+        read / size
+    })
+}
+
+unsafe extern "C" fn write_proc(
+    _file: *mut ffi::aiFile,
+    _buf: *const c_char,
+    _size: size_t,
+    _count: size_t,
+) -> size_t {
+    0
+}
+
+unsafe extern "C" fn tell_proc(file: *mut ffi::aiFile) -> size_t {
+    let stream = stream(file);
+    guard(0, || stream.seek(SeekFrom::Current(0)).map(|pos| pos as size_t).unwrap_or(0))
+}
+
+unsafe extern "C" fn size_proc(file: *mut ffi::aiFile) -> size_t {
+    let stream = stream(file);
+    guard(0, || stream_len(stream).map(|len| len as size_t).unwrap_or(0))
+}
+
+fn stream_len(stream: &mut Box<dyn ReadSeek>) -> io::Result<u64> {
+    let pos = stream.seek(SeekFrom::Current(0))?;
+    let end = stream.seek(SeekFrom::End(0))?;
+    stream.seek(SeekFrom::Start(pos))?;
+    Ok(end)
+}
+
+unsafe extern "C" fn seek_proc(
+    file: *mut ffi::aiFile,
+    offset: size_t,
+    origin: ffi::aiOrigin,
+) -> ffi::aiReturn {
+    // relative offsets are signed, assimp passes them wrapped to size_t
+    let pos = match origin {
+        ffi::aiOrigin::aiOrigin_SET => SeekFrom::Start(offset as u64),
+        ffi::aiOrigin::aiOrigin_CUR => SeekFrom::Current(offset as i64),
+        ffi::aiOrigin::aiOrigin_END => SeekFrom::End(offset as i64),
+        _ => return ffi::aiReturn::aiReturn_FAILURE,
+    };
+    let stream = stream(file);
+    match guard(None, || stream.seek(pos).ok()) {
+        Some(_) => ffi::aiReturn::aiReturn_SUCCESS,
+        None => ffi::aiReturn::aiReturn_FAILURE,
+    }
+}
+
+unsafe extern "C" fn flush_proc(_file: *mut ffi::aiFile) {}
@@ -1,38 +1,106 @@
+//! Rust bindings for the assimp 3D model importer.
+//!
+//! The common types are collected in `prelude`, everything else lives in
+//! the module it belongs to. The flat re-exports of all types at the crate
+//! root are deprecated and only kept for compatibility with older code.
+
 #[macro_use]
 extern crate bitflags;
 extern crate libc;
+#[cfg(feature = "log")]
+#[macro_use]
+extern crate log;
+pub extern crate assimp_import_sys as ffi;
 
 // TODO Naming? `prim`?
 //pub mod types;
 
-pub mod ffi;
-
 #[macro_use]
 mod macros;
 pub mod prim;
 
 pub mod anim;
 pub mod camera;
+pub mod config;
+pub mod data;
+pub mod export;
+pub mod importer;
+pub mod imposter;
+pub mod io;
 pub mod light;
+pub mod limits;
+pub mod logging;
 pub mod material;
 pub mod mesh;
 pub mod metadata;
 pub mod postprocess;
+pub mod prelude;
+pub mod skeleton;
 pub mod texture;
 pub mod scene;
 
-// TODO config.h, importerdesc.h
-
 pub const MAX_COLOR_SETS: usize = ffi::AI_MAX_NUMBER_OF_COLOR_SETS;
 pub const MAX_TEXTURE_COORDS: usize = ffi::AI_MAX_NUMBER_OF_TEXTURECOORDS;
 
+/// The version of the linked assimp library as (major, minor, revision).
+pub fn version() -> (u32, u32, u32) {
+    unsafe { (ffi::aiGetVersionMajor(), ffi::aiGetVersionMinor(), ffi::aiGetVersionRevision()) }
+}
+
+// Flat re-exports of (almost) everything, kept for compatibility with
+// older code. Use the modules or the prelude instead. The most common
+// names are shadowed by deprecated aliases below, so old code gets a
+// deprecation warning; the rest are only hidden from the docs.
+#[doc(hidden)]
 pub use anim::*;
+#[doc(hidden)]
 pub use camera::*;
+#[doc(hidden)]
+pub use config::PropertyStore;
+#[doc(hidden)]
+pub use export::*;
+#[doc(hidden)]
+pub use importer::*;
+#[doc(hidden)]
+pub use imposter::*;
+#[doc(hidden)]
+pub use io::FileSystem;
+#[doc(hidden)]
 pub use material::*;
+#[doc(hidden)]
 pub use light::*;
+#[doc(hidden)]
+pub use limits::*;
+#[doc(hidden)]
+pub use logging::{LogCapture, LogLevel, LogMessage};
+#[doc(hidden)]
 pub use mesh::*;
+#[doc(hidden)]
 pub use metadata::*;
+#[doc(hidden)]
 pub use postprocess::*;
+#[doc(hidden)]
 pub use scene::*;
-pub use texture::*;
+#[doc(hidden)]
+pub use skeleton::*;
 
+#[deprecated(note = "use `assimp_import::prelude::*` or `assimp_import::scene::Scene`")]
+pub type Scene = scene::Scene;
+#[deprecated(note = "use `assimp_import::prelude::*` or `assimp_import::scene::Node`")]
+pub type Node<'a> = scene::Node<'a>;
+#[deprecated(note = "use `assimp_import::prelude::*` or `assimp_import::mesh::Mesh`")]
+pub type Mesh<'a> = mesh::Mesh<'a>;
+#[deprecated(note = "use `assimp_import::prelude::*` or `assimp_import::material::Material`")]
+pub type Material<'a> = material::Material<'a>;
+#[deprecated(note = "use `assimp_import::prelude::*` or `assimp_import::material::TextureType`")]
+pub type TextureType = material::TextureType;
+#[deprecated(note = "use `assimp_import::prelude::*` or `assimp_import::anim::Animation`")]
+pub type Animation<'a> = anim::Animation<'a>;
+#[deprecated(note = "use `assimp_import::prelude::*` or `assimp_import::camera::Camera`")]
+pub type Camera<'a> = camera::Camera<'a>;
+#[deprecated(note = "use `assimp_import::prelude::*` or `assimp_import::light::Light`")]
+pub type Light<'a> = light::Light<'a>;
+#[deprecated(note = "use `assimp_import::prelude::*` or `assimp_import::texture::Texture`")]
+pub type Texture<'a> = texture::Texture<'a>;
+#[deprecated(note = "use `assimp_import::prelude::*` or `assimp_import::postprocess::PostProcessSteps`")]
+pub type PostProcessSteps = postprocess::PostProcessSteps;
@@ -1,4 +1,5 @@
-use prim::{self, Color3, Vector2, Vector3};
+use prim::{self, Aabb, Color3, Vector2, Vector3};
+use scene::Scene;
 use ffi;
 
 #[repr(u32)]
@@ -186,4 +187,150 @@ impl<'a> Light<'a> {
     pub fn size(&self) -> Vector2 {
         prim::vec2(self.raw().mSize)
     }
+
+    /// The distance at which the light's intensity drops below `cutoff`,
+    /// given the attenuation factors and the brightest diffuse color
+    /// component.
+    ///
+    /// `None` for lights with infinite range: directional and ambient
+    /// lights as well as lights without distance attenuation.
+    pub fn range(&self, cutoff: f32) -> Option<f32> {
+        match self.source_type() {
+            LightSourceType::Directional | LightSourceType::Ambient => return None,
+            _ => {}
+        }
+        let intensity = self.color_diffuse().iter().cloned().fold(0.0, f32::max);
+        let (c, l, q) = (self.attenuation_constant(), self.attenuation_linear(), self.attenuation_quadratic());
+        // solve q*d^2 + l*d + c = intensity / cutoff for d
+        let target = intensity / cutoff - c;
+        if target <= 0.0 {
+            return Some(0.0);
+        }
+        if q > 0.0 {
+            Some((-l + (l * l + 4.0 * q * target).sqrt()) / (2.0 * q))
+        } else if l > 0.0 {
+            Some(target / l)
+        } else {
+            None
+        }
+    }
+}
+
+// ++++++++++++++++++++ LightGrid ++++++++++++++++++++
+
+/// The intensity below which a light is considered to have no effect when
+/// building a `LightGrid`.
+pub const LIGHT_CUTOFF: f32 = 1.0 / 256.0;
+
+/// The most cells a `LightGrid` may have (16M, i.e. 256^3), so a small
+/// cell size over large bounds can't exhaust memory.
+pub const MAX_LIGHT_GRID_CELLS: usize = 1 << 24;
+
+/// A uniform grid assigning lights to the cells they affect, see
+/// `Scene::light_grid()`.
+///
+/// Light indices refer to `Scene::lights()`.
+#[derive(Debug, Clone)]
+pub struct LightGrid {
+    pub bounds: Aabb,
+    pub cell_size: f32,
+    /// The number of cells along x, y and z.
+    pub dims: [usize; 3],
+    /// The lights affecting each cell, x varying fastest.
+    pub cells: Vec<Vec<usize>>,
+    /// Lights affecting every cell (directional, ambient and unattenuated
+    /// lights). These are not repeated in `cells`.
+    pub global: Vec<usize>,
+}
+
+impl LightGrid {
+    /// `None` if `cell_size` isn't a positive, finite number or the grid
+    /// would have more than `MAX_LIGHT_GRID_CELLS` cells.
+    pub fn build(scene: &Scene, bounds: Aabb, cell_size: f32) -> Option<LightGrid> {
+        if !(cell_size > 0.0 && cell_size.is_finite()) {
+            return None;
+        }
+        let size = bounds.size();
+        let mut dims = [1; 3];
+        for axis in 0..3 {
+            dims[axis] = ((size[axis] / cell_size).ceil() as usize).max(1);
+        }
+        let count = dims[0].checked_mul(dims[1]).and_then(|n| n.checked_mul(dims[2]));
+        let count = match count {
+            Some(count) if count <= MAX_LIGHT_GRID_CELLS => count,
+            _ => return None,
+        };
+        let mut grid = LightGrid {
+            bounds,
+            cell_size,
+            dims,
+            cells: vec![Vec::new(); count],
+            global: Vec::new(),
+        };
+
+        for (idx, light) in scene.lights().iter().enumerate() {
+            let range = match light.range(LIGHT_CUTOFF) {
+                Some(range) => range,
+                None => {
+                    grid.global.push(idx);
+                    continue;
+                }
+            };
+            let transform = scene.find_node(light.name()).map_or(prim::IDENTITY, |n| n.global_transform());
+            let center = prim::transform_point(&transform, light.position());
+
+            // cells overlapping the light's bounding box, refined by a
+            // sphere/box distance test
+            let mut lo = [0; 3];
+            let mut hi = [0; 3];
+            let mut outside = false;
+            for axis in 0..3 {
+                let cell = |v: f32| ((v - bounds.min[axis]) / cell_size).floor();
+                let (a, b) = (cell(center[axis] - range), cell(center[axis] + range));
+                if b < 0.0 || a >= dims[axis] as f32 {
+                    outside = true;
+                    break;
+                }
+                lo[axis] = a.max(0.0) as usize;
+                hi[axis] = (b as usize).min(dims[axis] - 1);
+            }
+            if outside {
+                continue;
+            }
+            for z in lo[2]..hi[2] + 1 {
+                for y in lo[1]..hi[1] + 1 {
+                    for x in lo[0]..hi[0] + 1 {
+                        let cell_min = [
+                            bounds.min[0] + x as f32 * cell_size,
+                            bounds.min[1] + y as f32 * cell_size,
+                            bounds.min[2] + z as f32 * cell_size,
+                        ];
+                        let mut dist_sq = 0.0;
+                        for axis in 0..3 {
+                            let nearest = center[axis].max(cell_min[axis]).min(cell_min[axis] + cell_size);
+                            dist_sq += (center[axis] - nearest).powi(2);
+                        }
+                        if dist_sq <= range * range {
+                            grid.cells[(z * dims[1] + y) * dims[0] + x].push(idx);
+                        }
+                    }
+                }
+            }
+        }
+        Some(grid)
+    }
+
+    /// The lights affecting the cell containing `p`, excluding `global`.
+    /// Empty outside of the grid.
+    pub fn lights_at(&self, p: Vector3) -> &[usize] {
+        let mut idx = [0; 3];
+        for axis in 0..3 {
+            let cell = ((p[axis] - self.bounds.min[axis]) / self.cell_size).floor();
+            if cell < 0.0 || cell >= self.dims[axis] as f32 {
+                return &[];
+            }
+            idx[axis] = cell as usize;
+        }
+        &self.cells[(idx[2] * self.dims[1] + idx[1]) * self.dims[0] + idx[0]]
+    }
 }
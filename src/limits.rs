@@ -0,0 +1,98 @@
+use io::{FileSystem, ReadSeek};
+use scene::Scene;
+use std::fs::File;
+use std::io;
+
+/// Hard limits for importing untrusted files.
+///
+/// Unset limits are not enforced. Only the file size is enforced during
+/// the import: `Scene::from_file_with_limits()` refuses to open the file,
+/// or any file it references, if it is larger than `max_file_size`.
+///
+/// All other limits are checked only after assimp has fully imported and
+/// post-processed the file, before the scene is handed out. They keep
+/// oversized scenes away from the application, but they don't protect
+/// against memory blow-ups, decompression bombs or deep recursion inside
+/// assimp during the import itself. Import untrusted files in a separate
+/// process with OS-level memory limits for that.
+#[derive(Debug, Clone, Default)]
+pub struct ImportLimits {
+    /// Maximum size in bytes of the file and of every file it references.
+    pub max_file_size: Option<u64>,
+    /// Maximum depth of the node hierarchy, the root node has depth 1.
+    pub max_node_depth: Option<usize>,
+    /// Maximum number of meshes.
+    pub max_meshes: Option<usize>,
+    /// Maximum number of vertices of all meshes combined.
+    pub max_vertices: Option<usize>,
+    /// Maximum size of all embedded textures combined, in bytes.
+    pub max_texture_bytes: Option<usize>,
+}
+
+impl ImportLimits {
+    /// Checks the size of a file (or buffer) before importing it.
+    pub fn check_file_size(&self, size: u64) -> Result<(), String> {
+        match self.max_file_size {
+            Some(max) if size > max => Err(format!("file size of {} bytes exceeds the limit of {}", size, max)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks an imported scene against all limits except the file size.
+    pub fn check_scene(&self, scene: &Scene) -> Result<(), String> {
+        let meshes = scene.meshes().len();
+        if let Some(max) = self.max_meshes {
+            if meshes > max {
+                return Err(format!("{} meshes exceed the limit of {}", meshes, max));
+            }
+        }
+
+        if let Some(max) = self.max_vertices {
+            let vertices: usize = scene.meshes().iter().map(|m| m.vertices().len()).sum();
+            if vertices > max {
+                return Err(format!("{} vertices exceed the limit of {}", vertices, max));
+            }
+        }
+
+        if let Some(max) = self.max_texture_bytes {
+            let bytes: usize = scene.textures().iter().map(|t| t.as_bytes().len()).sum();
+            if bytes > max {
+                return Err(format!("{} bytes of embedded textures exceed the limit of {}", bytes, max));
+            }
+        }
+
+        if let (Some(max), Some(root)) = (self.max_node_depth, scene.root_node()) {
+            // iterative, hostile files may nest deep enough to overflow the stack
+            let mut stack = vec![(root, 1)];
+            while let Some((node, depth)) = stack.pop() {
+                if depth > max {
+                    return Err(format!("node hierarchy exceeds the depth limit of {}", max));
+                }
+                stack.extend(node.children_iter().map(|child| (child, depth + 1)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads from disk, refusing files larger than the limit.
+///
+/// Assimp only reports that a file couldn't be opened, so the reason is
+/// kept in `exceeded` to report it instead.
+pub struct SizeLimitedFileSystem<'a> {
+    pub limits: &'a ImportLimits,
+    pub exceeded: Option<String>,
+}
+
+impl<'a> FileSystem for SizeLimitedFileSystem<'a> {
+    fn open(&mut self, path: &str) -> io::Result<Box<dyn ReadSeek>> {
+        let file = File::open(path)?;
+        let size = file.metadata()?.len();
+        if let Err(e) = self.limits.check_file_size(size) {
+            let e = format!("{}: {}", path, e);
+            self.exceeded = Some(e.clone());
+            return Err(io::Error::new(io::ErrorKind::Other, e));
+        }
+        Ok(Box::new(file))
+    }
+}
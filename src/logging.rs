@@ -0,0 +1,175 @@
+//! Capturing assimp's log output, see `LogCapture`.
+//!
+//! Assimp has a single, process wide logger. While a `LogCapture` is
+//! alive, it receives the messages of every import running in the
+//! process, not only those started on the current thread.
+
+use ffi;
+use std::ffi::CStr;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Mutex;
+use std::mem;
+use libc::c_char;
+
+/// Severity of a `LogMessage`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    /// Only emitted with verbose logging enabled, see `set_verbose_logging()`.
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+/// A single message emitted by assimp.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogMessage {
+    pub level: LogLevel,
+    /// The message without assimp's severity and thread prefix.
+    pub message: String,
+}
+
+impl LogMessage {
+    /// Parses a line as formatted by assimp's default logger, e.g.
+    /// `"Warn,  T0: Found a matching importer ..."`.
+    fn parse(line: &str) -> LogMessage {
+        let line = line.trim_end_matches(|c| c == '\n' || c == '\r');
+        let levels = [
+            ("Debug,", LogLevel::Debug),
+            ("Info,", LogLevel::Info),
+            ("Warn,", LogLevel::Warn),
+            ("Error,", LogLevel::Error),
+        ];
+        for &(prefix, level) in &levels {
+            if line.starts_with(prefix) {
+                let rest = &line[prefix.len()..];
+                let message = match rest.find(": ") {
+                    Some(end) => &rest[end + 2..],
+                    None => rest.trim_start(),
+                };
+                return LogMessage { level, message: message.to_owned() };
+            }
+        }
+        // custom loggers, or messages assimp writes unformatted
+        LogMessage { level: LogLevel::Info, message: line.to_owned() }
+    }
+}
+
+/// Enables or disables debug messages for all log streams.
+pub fn set_verbose_logging(verbose: bool) {
+    unsafe { ffi::aiEnableVerboseLogging(verbose as ffi::aiBool) }
+}
+
+enum Sink {
+    Collect(Mutex<Vec<LogMessage>>),
+    #[cfg(feature = "log")]
+    Forward,
+}
+
+/// A log stream attached to assimp for as long as it is alive.
+///
+/// ```ignore
+/// let log = LogCapture::attach();
+/// let scene = Scene::from_path("model.fbx", PostProcessSteps::empty());
+/// for msg in log.take() {
+///     println!("{:?}: {}", msg.level, msg.message);
+/// }
+/// ```
+pub struct LogCapture {
+    stream: ffi::aiLogStream,
+    sink: Box<Sink>,
+}
+
+impl LogCapture {
+    /// Starts collecting messages, retrieve them with `take()`.
+    pub fn attach() -> LogCapture {
+        Self::with_sink(Sink::Collect(Mutex::new(Vec::new())))
+    }
+
+    /// Starts forwarding messages to the `log` crate, with target
+    /// `"assimp"`.
+    #[cfg(feature = "log")]
+    pub fn forward() -> LogCapture {
+        Self::with_sink(Sink::Forward)
+    }
+
+    fn with_sink(sink: Sink) -> LogCapture {
+        let mut sink = Box::new(sink);
+        let stream = ffi::aiLogStream {
+            callback: Some(log_callback),
+            user: &mut *sink as *mut Sink as *mut c_char,
+        };
+        unsafe { ffi::aiAttachLogStream(&stream) };
+        LogCapture { stream, sink }
+    }
+
+    /// Removes and returns the messages collected so far.
+    ///
+    /// Always empty for a capture created with `forward()`.
+    pub fn take(&self) -> Vec<LogMessage> {
+        match *self.sink {
+            Sink::Collect(ref messages) => {
+                let mut messages = messages.lock().unwrap_or_else(|e| e.into_inner());
+                mem::replace(&mut *messages, Vec::new())
+            }
+            #[cfg(feature = "log")]
+            Sink::Forward => Vec::new(),
+        }
+    }
+}
+
+impl Drop for LogCapture {
+    fn drop(&mut self) {
+        unsafe { ffi::aiDetachLogStream(&self.stream) };
+    }
+}
+
+unsafe extern "C" fn log_callback(msg: *const c_char, user: *mut c_char) {
+    let sink = &*(user as *const Sink);
+    let msg = LogMessage::parse(&CStr::from_ptr(msg).to_string_lossy());
+    // unwinding into assimp is undefined behaviour, drop the message instead
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| sink.push(msg)));
+}
+
+impl Sink {
+    fn push(&self, msg: LogMessage) {
+        match *self {
+            Sink::Collect(ref messages) => {
+                // a poisoned lock only means another callback panicked
+                // while pushing, the messages are still fine
+                let mut messages = messages.lock().unwrap_or_else(|e| e.into_inner());
+                messages.push(msg);
+            }
+            #[cfg(feature = "log")]
+            Sink::Forward => {
+                let level = match msg.level {
+                    LogLevel::Debug => ::log::Level::Debug,
+                    LogLevel::Info => ::log::Level::Info,
+                    LogLevel::Warn => ::log::Level::Warn,
+                    LogLevel::Error => ::log::Level::Error,
+                };
+                log!(target: "assimp", level, "{}", msg.message);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_levels() {
+        let msg = LogMessage::parse("Warn,  T0: Found a matching importer\n");
+        assert_eq!(msg, LogMessage { level: LogLevel::Warn, message: "Found a matching importer".to_owned() });
+        assert_eq!(LogMessage::parse("Debug, T0: a: b").message, "a: b");
+        assert_eq!(LogMessage::parse("Info,  T0: Load file").level, LogLevel::Info);
+        assert_eq!(LogMessage::parse("Error, T0: Unable to open file\r\n").level, LogLevel::Error);
+    }
+
+    #[test]
+    fn parse_unformatted() {
+        assert_eq!(LogMessage::parse("Error, no thread"), LogMessage { level: LogLevel::Error, message: "no thread".to_owned() });
+        assert_eq!(LogMessage::parse("plain text\n"), LogMessage { level: LogLevel::Info, message: "plain text".to_owned() });
+    }
+}
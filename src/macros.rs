@@ -12,6 +12,9 @@ macro_rules! ai_type {
             pub unsafe fn slice<'a>(ptr: *mut $raw_ty, len: ::libc::c_uint) -> &'a [Self] {
                 $crate::prim::slice::<$raw_ty, Self>(ptr, len)
             }
+
+            /// The underlying FFI struct.
+            pub fn raw(&self) -> &$raw_ty { &self.raw }
         }
 
     };
@@ -39,8 +42,14 @@ macro_rules! ai_ptr_type {
                 $crate::prim::slice::<*mut $raw_ty, Self>(ptr, len)
             }
 
-            #[doc(hidden)]
-            pub fn raw(&self) -> &$raw_ty { unsafe { &*self.ptr } }
+            /// The underlying FFI struct, as an escape hatch for data the
+            /// safe API doesn't cover yet.
+            ///
+            /// The struct is owned by the `Scene` this object was obtained
+            /// from and lives as long as it. It must be treated as
+            /// read-only, and pointers taken from its fields must not be
+            /// used after the scene was dropped.
+            pub fn raw(&self) -> &'a $raw_ty { unsafe { &*self.ptr } }
 
             #[doc(hidden)]
             // TODO Naming: get_ptr()
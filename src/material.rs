@@ -1,6 +1,8 @@
-use prim::{self, Color4, Vector2, Vector3};
+use prim::{self, Color3, Color4, Vector2, Vector3};
+use scene::Scene;
 use ffi;
 use std::{mem, ptr, slice, str};
+use std::path::PathBuf;
 use libc::{c_uint, c_int, c_char};
 
 pub type TextureIdx = c_uint;
@@ -123,7 +125,7 @@ ai_impl_enum!(TextureMapping, c_uint);
 /// and the artists working on models have to conform to this specification,
 /// regardless which 3D tool they're using.
 #[repr(u32)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TextureType {
     /// Dummy value.
     ///
@@ -437,11 +439,146 @@ pub struct MaterialProperties {
     //TODO pub other: BTreeMap<String, ?>,
 }
 
-// TODO
-//pub enum TextureRef {
-//  Embedded(TextureIdx),
-//  External(PathBuf),
-//}
+impl MaterialProperties {
+    /// Derives the alpha mode from the opacity and blend mode.
+    ///
+    /// Additive blending and an opacity below 1 result in `AlphaMode::Blend`,
+    /// everything else is considered opaque. Use `Material::alpha_mode()`
+    /// to also take opacity textures and format specific keys into account.
+    pub fn alpha_mode(&self) -> AlphaMode {
+        match self.blend_mode {
+            BlendMode::Additive => AlphaMode::Blend,
+            BlendMode::Default if self.opacity < 1.0 => AlphaMode::Blend,
+            BlendMode::Default => AlphaMode::Opaque,
+        }
+    }
+
+    /// Converts the classic material parameters to values suitable for
+    /// physically based shading.
+    ///
+    /// * `f0` is the reflectance at normal incidence derived from the index
+    ///   of refraction: `((n - 1) / (n + 1))^2`. Indices of 1 or below are
+    ///   treated as unspecified (assimp reports 1 for materials without
+    ///   `$mat.refracti`) and replaced by `DEFAULT_IOR`, giving 0.04.
+    /// * `transmission` is `1 - opacity`, tinted by the transparent color
+    ///   if the material specifies a non-black one.
+    /// * The emissive color is split into a color with a maximum component
+    ///   of 1 and a separate strength, so HDR emission survives clamping.
+    pub fn to_physical(&self) -> PhysicalProperties {
+        let ior = if self.refracti > 1.0 { self.refracti } else { DEFAULT_IOR };
+        let f0 = ((ior - 1.0) / (ior + 1.0)).powi(2);
+
+        let transmission = (1.0 - self.opacity).max(0.0).min(1.0);
+        let transparent = [self.color_transparent[0], self.color_transparent[1], self.color_transparent[2]];
+        let transmission_color = if transparent.iter().any(|&c| c > 0.0) { transparent } else { [1.0; 3] };
+
+        let emissive = [self.color_emissive[0], self.color_emissive[1], self.color_emissive[2]];
+        let emissive_strength = emissive.iter().cloned().fold(0.0, f32::max);
+        let emissive_color = if emissive_strength > 0.0 {
+            [emissive[0] / emissive_strength, emissive[1] / emissive_strength, emissive[2] / emissive_strength]
+        } else {
+            [0.0; 3]
+        };
+
+        PhysicalProperties {
+            base_color: [self.color_diffuse[0], self.color_diffuse[1], self.color_diffuse[2], self.opacity],
+            ior,
+            f0,
+            transmission,
+            transmission_color,
+            emissive_color,
+            emissive_strength,
+        }
+    }
+}
+
+/// The index of refraction assumed for materials which don't specify a valid one.
+pub const DEFAULT_IOR: f32 = 1.5;
+
+/// Material parameters for physically based shading.
+///
+/// See `MaterialProperties::to_physical()`.
+#[derive(Debug, Clone, Copy)]
+pub struct PhysicalProperties {
+    /// Diffuse color, with the opacity as alpha.
+    pub base_color: Color4,
+    /// Index of refraction.
+    pub ior: f32,
+    /// Reflectance at normal incidence.
+    pub f0: f32,
+    /// Fraction of light transmitted through the surface, in [0, 1].
+    pub transmission: f32,
+    /// Tint of the transmitted light.
+    pub transmission_color: Color3,
+    /// Emissive color, normalized to a maximum component of 1.
+    pub emissive_color: Color3,
+    /// Multiplier for `emissive_color`.
+    pub emissive_strength: f32,
+}
+
+/// How the alpha channel of a material is to be interpreted.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlphaMode {
+    /// The alpha value is ignored.
+    Opaque,
+
+    /// Fragments with an alpha value below `cutoff` are discarded,
+    /// all others are rendered opaque.
+    Mask { cutoff: f32 },
+
+    /// The alpha value is used to blend with the framebuffer.
+    Blend,
+}
+
+/// The alpha cutoff glTF assumes if none is specified.
+pub const DEFAULT_ALPHA_CUTOFF: f32 = 0.5;
+
+bitflags!{
+    /// Identifies the fields of `MaterialProperties`.
+    ///
+    /// Returned by `Material::material_properties_checked()` to tell apart
+    /// values read from the material and values which fell back to their
+    /// defaults because the corresponding key was missing.
+    pub flags MaterialKeys: u32 {
+        const NAME = 0x1,
+        const TWOSIDED = 0x2,
+        const SHADING_MODE = 0x4,
+        const WIREFRAME = 0x8,
+        const BLEND_MODE = 0x10,
+        const OPACITY = 0x20,
+        const BUMPSCALING = 0x40,
+        const SHININESS = 0x80,
+        const SHININESS_STRENGTH = 0x100,
+        const REFLECTIVITY = 0x200,
+        const REFRACTI = 0x400,
+        const COLOR_DIFFUSE = 0x800,
+        const COLOR_AMBIENT = 0x1000,
+        const COLOR_SPECULAR = 0x2000,
+        const COLOR_EMISSIVE = 0x4000,
+        const COLOR_TRANSPARENT = 0x8000,
+        const COLOR_REFLECTIVE = 0x10000,
+    }
+}
+
+/// The texture a `TextureProperties::texture_ref` points to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TextureRef {
+    /// Index into `Scene::textures()`, written as `*<index>` by assimp.
+    Embedded(TextureIdx),
+    /// A file path, usually relative to the imported file.
+    External(PathBuf),
+}
+
+impl TextureRef {
+    pub fn parse(texture_ref: &str) -> TextureRef {
+        if texture_ref.starts_with('*') {
+            if let Ok(idx) = texture_ref[1..].parse() {
+                return TextureRef::Embedded(idx);
+            }
+        }
+        TextureRef::External(PathBuf::from(texture_ref))
+    }
+}
 
 /// TODO
 #[derive(Debug, Clone)]
@@ -451,11 +588,70 @@ pub struct TextureProperties {
     pub uv_index: Option<u32>, 
     pub blend: f32,
     pub op: TextureOp,
-    pub map_mode: [TextureMapMode; 2],
+    /// Wrapping modes for the u, v and w axes.
+    ///
+    /// The w mode is only relevant for 3D texture coordinates (volume and
+    /// cube maps, see `Mesh::num_uv_components()`) and defaults to `Wrap`.
+    pub map_mode: [TextureMapMode; 3],
     pub flags: TextureFlags,
     //TODO pub other: BTreeMap<String, ?>,
 }
 
+impl TextureProperties {
+    /// Wrapping modes for the u and v axes only.
+    pub fn map_mode_uv(&self) -> [TextureMapMode; 2] {
+        [self.map_mode[0], self.map_mode[1]]
+    }
+}
+
+/// A face of a cube map.
+///
+/// Faces are numbered in the order used by OpenGL and Direct3D,
+/// +X, -X, +Y, -Y, +Z, -Z.
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CubeFace {
+    PositiveX = 0x0,
+    NegativeX = 0x1,
+    PositiveY = 0x2,
+    NegativeY = 0x3,
+    PositiveZ = 0x4,
+    NegativeZ = 0x5,
+}
+
+impl CubeFace {
+    /// Maps a 3D texture coordinate (a direction) to the cube face it hits
+    /// and the 2D coordinate on that face, in [0...1].
+    ///
+    /// Follows the OpenGL convention (section "Cube Map Texture Selection"),
+    /// which Direct3D shares.
+    pub fn from_direction(dir: Vector3) -> (CubeFace, Vector2) {
+        let [x, y, z] = dir;
+        let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+        let (face, sc, tc, ma) = if ax >= ay && ax >= az {
+            if x >= 0.0 {
+                (CubeFace::PositiveX, -z, -y, ax)
+            } else {
+                (CubeFace::NegativeX, z, -y, ax)
+            }
+        } else if ay >= az {
+            if y >= 0.0 {
+                (CubeFace::PositiveY, x, z, ay)
+            } else {
+                (CubeFace::NegativeY, x, -z, ay)
+            }
+        } else if z >= 0.0 {
+            (CubeFace::PositiveZ, x, -y, az)
+        } else {
+            (CubeFace::NegativeZ, -x, -y, az)
+        };
+        if ma == 0.0 {
+            return (face, [0.5, 0.5]);
+        }
+        (face, [(sc / ma + 1.0) * 0.5, (tc / ma + 1.0) * 0.5])
+    }
+}
+
 ai_ptr_type!{
     /// TODO Docs
     type Material: ffi::aiMaterial;
@@ -469,6 +665,15 @@ impl<'a> Material<'a> {
     */
 
     pub fn material_properties(&self) -> MaterialProperties {
+        self.material_properties_checked().0
+    }
+
+    /// Like `material_properties()`, but also reports which fields were
+    /// actually present in the material.
+    ///
+    /// Fields whose key is missing from the material keep their default
+    /// value and have their flag cleared in the returned `MaterialKeys`.
+    pub fn material_properties_checked(&self) -> (MaterialProperties, MaterialKeys) {
         let mut name = ffi::aiString::default();
         let mut twosided: c_int = 0;
         let mut shading_mode: c_int = ShadingMode::Gouraud as u32 as i32;
@@ -486,62 +691,73 @@ impl<'a> Material<'a> {
         let mut color_emissive = ffi::aiColor4D::default();
         let mut color_transparent = ffi::aiColor4D::default();
         let mut color_reflective = ffi::aiColor4D::default(); // TODO default?
+        let mut found = MaterialKeys::empty();
 
         unsafe {
-            ffi::aiGetMaterialString(
+            use ffi::aiReturn::aiReturn_SUCCESS;
+
+            macro_rules! check {
+                ($key:expr, $call:expr) => {
+                    if $call == aiReturn_SUCCESS {
+                        found.insert($key);
+                    }
+                }
+            }
+
+            check!(NAME, ffi::aiGetMaterialString(
                 self.as_ptr(), "?mat.name\0".as_ptr() as *const c_char, 0, 0, &mut name
-            );
-            ffi::aiGetMaterialIntegerArray(
+            ));
+            check!(TWOSIDED, ffi::aiGetMaterialIntegerArray(
                 self.as_ptr(), "$mat.twosided\0".as_ptr() as *const c_char, 0, 0, &mut twosided, ptr::null_mut()
-            );
-            ffi::aiGetMaterialIntegerArray(
+            ));
+            check!(SHADING_MODE, ffi::aiGetMaterialIntegerArray(
                 self.as_ptr(), "$mat.shadingm\0".as_ptr() as *const c_char, 0, 0, &mut shading_mode, ptr::null_mut()
-            );
-            ffi::aiGetMaterialIntegerArray(
+            ));
+            check!(WIREFRAME, ffi::aiGetMaterialIntegerArray(
                 self.as_ptr(), "$mat.wireframe\0".as_ptr() as *const c_char, 0, 0, &mut wireframe, ptr::null_mut()
-            );
-            ffi::aiGetMaterialIntegerArray(
+            ));
+            check!(BLEND_MODE, ffi::aiGetMaterialIntegerArray(
                 self.as_ptr(), "$mat.blend\0".as_ptr() as *const c_char, 0, 0, &mut blend_mode, ptr::null_mut()
-            );
-            ffi::aiGetMaterialFloatArray(
+            ));
+            check!(OPACITY, ffi::aiGetMaterialFloatArray(
                 self.as_ptr(), "$mat.opacity\0".as_ptr() as *const c_char, 0, 0, &mut opacity, ptr::null_mut()
-            );
-            ffi::aiGetMaterialFloatArray(
+            ));
+            check!(BUMPSCALING, ffi::aiGetMaterialFloatArray(
                 self.as_ptr(), "$mat.bumpscaling\0".as_ptr() as *const c_char, 0, 0, &mut bumpscaling, ptr::null_mut()
-            );
-            ffi::aiGetMaterialFloatArray(
+            ));
+            check!(SHININESS, ffi::aiGetMaterialFloatArray(
                 self.as_ptr(), "$mat.shininess\0".as_ptr() as *const c_char, 0, 0, &mut shininess, ptr::null_mut()
-            );
-            ffi::aiGetMaterialFloatArray(
+            ));
+            check!(SHININESS_STRENGTH, ffi::aiGetMaterialFloatArray(
                 self.as_ptr(), "$mat.shinpercent\0".as_ptr() as *const c_char, 0, 0, &mut shininess_strength, ptr::null_mut()
-            );
-            ffi::aiGetMaterialFloatArray(
+            ));
+            check!(REFLECTIVITY, ffi::aiGetMaterialFloatArray(
                 self.as_ptr(), "$mat.reflectivity\0".as_ptr() as *const c_char, 0, 0, &mut reflectivity, ptr::null_mut()
-            );
-            ffi::aiGetMaterialFloatArray(
+            ));
+            check!(REFRACTI, ffi::aiGetMaterialFloatArray(
                 self.as_ptr(), "$mat.refracti\0".as_ptr() as *const c_char, 0, 0, &mut refracti, ptr::null_mut()
-            );
-            ffi::aiGetMaterialColor(
+            ));
+            check!(COLOR_DIFFUSE, ffi::aiGetMaterialColor(
                 self.as_ptr(), "$clr.diffuse\0".as_ptr() as *const c_char, 0, 0, &mut color_diffuse
-            );
-            ffi::aiGetMaterialColor(
+            ));
+            check!(COLOR_AMBIENT, ffi::aiGetMaterialColor(
                 self.as_ptr(), "$clr.ambient\0".as_ptr() as *const c_char, 0, 0, &mut color_ambient
-            );
-            ffi::aiGetMaterialColor(
+            ));
+            check!(COLOR_SPECULAR, ffi::aiGetMaterialColor(
                 self.as_ptr(), "$clr.specular\0".as_ptr() as *const c_char, 0, 0, &mut color_specular
-            );
-            ffi::aiGetMaterialColor(
+            ));
+            check!(COLOR_EMISSIVE, ffi::aiGetMaterialColor(
                 self.as_ptr(), "$clr.emissive\0".as_ptr() as *const c_char, 0, 0, &mut color_emissive
-            );
-            ffi::aiGetMaterialColor(
+            ));
+            check!(COLOR_TRANSPARENT, ffi::aiGetMaterialColor(
                 self.as_ptr(), "$clr.transparent\0".as_ptr() as *const c_char, 0, 0, &mut color_transparent
-            );
-            ffi::aiGetMaterialColor(
+            ));
+            check!(COLOR_REFLECTIVE, ffi::aiGetMaterialColor(
                 self.as_ptr(), "$clr.reflective\0".as_ptr() as *const c_char, 0, 0, &mut color_reflective
-            );
+            ));
 
-            MaterialProperties {
-                name: prim::str(&name).unwrap().to_owned(),
+            let props = MaterialProperties {
+                name: prim::str(&name).unwrap_or("").to_owned(),
                 twosided: twosided != 0,
                 shading_mode: ShadingMode::from_ffi(shading_mode as c_uint),
                 wireframe: wireframe != 0,
@@ -558,7 +774,8 @@ impl<'a> Material<'a> {
                 color_emissive: prim::col4(color_emissive),
                 color_transparent: prim::col4(color_transparent),
                 color_reflective: prim::col4(color_reflective),
-            }
+            };
+            (props, found)
         }
     }
 
@@ -600,6 +817,12 @@ impl<'a> Material<'a> {
                 &mut flags,
             ) == aiReturn_SUCCESS;
 
+            // aiGetMaterialTexture() only reads the u and v modes
+            let mut map_mode_w: c_int = ffi::aiTextureMapMode::aiTextureMapMode_Wrap as c_int;
+            ffi::aiGetMaterialIntegerArray(
+                self.as_ptr(), "$tex.mapmodew\0".as_ptr() as *const c_char, tex_ty as c_uint, idx, &mut map_mode_w, ptr::null_mut()
+            );
+
             if ok {
                 Some(TextureProperties {
                     texture_ref: prim::str(&path).unwrap().to_owned(),
@@ -607,7 +830,11 @@ impl<'a> Material<'a> {
                     uv_index: if uv_index != !0 { Some(uv_index) } else { None },
                     blend,
                     op: TextureOp::from_ffi(op as u32), 
-                    map_mode: [TextureMapMode::from_ffi(map_mode[0] as u32), TextureMapMode::from_ffi(map_mode[1] as u32)],
+                    map_mode: [
+                        TextureMapMode::from_ffi(map_mode[0] as u32),
+                        TextureMapMode::from_ffi(map_mode[1] as u32),
+                        TextureMapMode::from_ffi(map_mode_w as u32),
+                    ],
                     flags: TextureFlags::from_bits(flags).unwrap(),
                 })
             } else {
@@ -615,5 +842,600 @@ impl<'a> Material<'a> {
             }
         }
     }
+    /// The environment maps referenced by this material.
+    ///
+    /// Reflection textures are classified by their mapping and file names:
+    /// six textures following a common face naming convention (`_posx`/`_negx`,
+    /// `_px`/`_nx`, `_right`/`_left`, `_rt`/`_lf`, ...) are combined into a
+    /// single `EnvironmentMap::CubeFaces`, box-mapped textures are treated as
+    /// single-file cube maps, and sphere-mapped, `.hdr` or
+    /// `latlong`/`equirect`/`panorama` textures as equirectangular maps.
+    pub fn environment_maps(&self) -> Vec<EnvironmentMap> {
+        let mut ret = Vec::new();
+        let mut cubes: Vec<(String, usize, [Option<String>; 6])> = Vec::new();
+        let mut singles = Vec::new();
+
+        for tex in self.textures(TextureType::Reflection) {
+            match cube_face(&tex.texture_ref) {
+                Some((prefix, convention, face)) => {
+                    let pos = cubes.iter().position(|c| c.0 == prefix && c.1 == convention);
+                    let pos = pos.unwrap_or_else(|| {
+                        cubes.push((prefix, convention, Default::default()));
+                        cubes.len() - 1
+                    });
+                    cubes[pos].2[face] = Some(tex.texture_ref.clone());
+                }
+                None => singles.push(tex),
+            }
+        }
+
+        for (_, _, faces) in cubes {
+            if faces.iter().all(|f| f.is_some()) {
+                let mut faces = faces.iter().cloned().map(Option::unwrap);
+                let faces = [
+                    faces.next().unwrap(), faces.next().unwrap(), faces.next().unwrap(),
+                    faces.next().unwrap(), faces.next().unwrap(), faces.next().unwrap(),
+                ];
+                ret.push(EnvironmentMap::CubeFaces(faces));
+            } else {
+                // incomplete set, fall back to treating each face separately
+                ret.extend(faces.iter().filter_map(|f| f.clone()).map(EnvironmentMap::Other));
+            }
+        }
+
+        for tex in singles {
+            let lower = tex.texture_ref.to_lowercase();
+            let env = match tex.mapping {
+                TextureMapping::Box => EnvironmentMap::Cube(tex.texture_ref),
+                TextureMapping::Sphere => EnvironmentMap::Equirectangular(tex.texture_ref),
+                _ if lower.ends_with(".hdr") || EQUIRECT_HINTS.iter().any(|h| lower.contains(h)) => {
+                    EnvironmentMap::Equirectangular(tex.texture_ref)
+                }
+                _ => EnvironmentMap::Other(tex.texture_ref),
+            };
+            ret.push(env);
+        }
+        ret
+    }
+
+    /// Derives the alpha mode of this material.
+    ///
+    /// In order of precedence:
+    ///
+    /// 1. The glTF `alphaMode`/`alphaCutoff` keys, if present.
+    /// 2. `MaterialProperties::alpha_mode()`, if it yields blending.
+    /// 3. An opacity texture, or a diffuse texture flagged `USE_ALPHA`,
+    ///    results in `AlphaMode::Mask` with the default cutoff.
+    /// 4. `AlphaMode::Opaque`.
+    pub fn alpha_mode(&self) -> AlphaMode {
+        let cutoff = self.get_float("$mat.gltf.alphaCutoff\0").unwrap_or(DEFAULT_ALPHA_CUTOFF);
+        match self.get_string("$mat.gltf.alphaMode\0").as_ref().map(|s| &s[..]) {
+            Some("OPAQUE") => return AlphaMode::Opaque,
+            Some("MASK") => return AlphaMode::Mask { cutoff },
+            Some("BLEND") => return AlphaMode::Blend,
+            _ => {}
+        }
+
+        if let AlphaMode::Blend = self.material_properties().alpha_mode() {
+            return AlphaMode::Blend;
+        }
+
+        let diffuse_alpha = self.textures(TextureType::Diffuse).iter().any(|t| t.flags.contains(USE_ALPHA));
+        if self.count_texture_properties(TextureType::Opacity) > 0 || diffuse_alpha {
+            return AlphaMode::Mask { cutoff };
+        }
+        AlphaMode::Opaque
+    }
+
+    /// Scale factor for displacement, bump and parallax mapping, `1.0`
+    /// meaning unscaled.
+    ///
+    /// * glTF (recognized by its `$mat.gltf.*` keys): the normal texture's
+    ///   `scale`, which assimp stores as `$tex.scale` of the normal map
+    ///   rather than as bump scaling.
+    /// * Everything else: the `$mat.bumpscaling` key. The importers store
+    ///   it as a plain factor already (OBJ's `-bm` multiplier, the 3DS/ASE
+    ///   bump amount converted from percent, Blender's normal map factor),
+    ///   so it is returned as-is.
+    ///
+    /// Unlike `MaterialProperties::bumpscaling`, a missing value gives
+    /// `1.0` instead of `0.0`.
+    pub fn displacement_scale(&self) -> f32 {
+        if self.get_string("$mat.gltf.alphaMode\0").is_some() {
+            return self.get_texture_float("$tex.scale\0", TextureType::Normals, 0).unwrap_or(1.0);
+        }
+        self.get_float("$mat.bumpscaling\0").unwrap_or(1.0)
+    }
+
+    /// The textures affecting surface detail, with height and normal maps
+    /// told apart.
+    ///
+    /// Many formats (most prominently OBJ's `map_bump`) store normal maps in
+    /// the height slot. A height texture is reported as a normal map if the
+    /// material has no dedicated normal map and its file name carries a
+    /// normal map hint (`normal`, `_nrm`, `_norm`, `_n`). Displacement textures
+    /// are always reported as height maps.
+    pub fn bump_maps(&self) -> Vec<BumpMap> {
+        let has_normals = self.count_texture_properties(TextureType::Normals) > 0;
+        let mut ret = Vec::new();
+
+        for (idx, tex) in self.indexed_textures(TextureType::Normals) {
+            ret.push(BumpMap { source: TextureType::Normals, idx, kind: BumpMapKind::Normals, texture: tex });
+        }
+        for (idx, tex) in self.indexed_textures(TextureType::Height) {
+            let kind = if !has_normals && is_normal_map_name(&tex.texture_ref) {
+                BumpMapKind::Normals
+            } else {
+                BumpMapKind::Height
+            };
+            ret.push(BumpMap { source: TextureType::Height, idx, kind, texture: tex });
+        }
+        for (idx, tex) in self.indexed_textures(TextureType::Displacement) {
+            ret.push(BumpMap { source: TextureType::Displacement, idx, kind: BumpMapKind::Height, texture: tex });
+        }
+        ret
+    }
+
+    /// The ambient occlusion texture of this material, if any.
+    ///
+    /// Lightmap textures are used first (glTF's occlusion texture is
+    /// imported into this slot), followed by ambient and unknown textures
+    /// whose file name carries an occlusion hint (`ao`, `occlusion`, ...).
+    /// Packed occlusion/roughness/metallic textures and textures named
+    /// like dedicated AO maps store occlusion in the red channel, as do all
+    /// glTF occlusion textures; other lightmaps are read in full color. The
+    /// strength is taken from the glTF `$tex.strength` key and defaults
+    /// to 1.
+    pub fn occlusion(&self) -> Option<Occlusion> {
+        let lightmap = self
+            .indexed_textures(TextureType::Lightmap)
+            .into_iter()
+            .next()
+            .map(|(idx, t)| (TextureType::Lightmap, idx, t));
+        let hinted = || {
+            [TextureType::Ambient, TextureType::Unknown]
+                .iter()
+                .flat_map(|&ty| self.indexed_textures(ty).into_iter().map(move |(idx, t)| (ty, idx, t)))
+                .find(|&(_, _, ref t)| is_occlusion_map_name(&t.texture_ref))
+        };
+        let (source, idx, texture) = lightmap.or_else(hinted)?;
+
+        // only glTF writes an occlusion strength, and glTF always uses red
+        let strength = self.get_texture_float("$tex.strength\0", source, idx);
+        let channel = if strength.is_some() || is_occlusion_map_name(&texture.texture_ref) {
+            TextureChannel::R
+        } else {
+            TextureChannel::Rgb
+        };
+        Some(Occlusion { source, idx, texture, channel, strength: strength.unwrap_or(1.0) })
+    }
+
+    /// Reads a single string property. `key` must be NUL-terminated.
+    fn get_string(&self, key: &str) -> Option<String> {
+        let mut ret = ffi::aiString::default();
+        unsafe {
+            use ffi::aiReturn::aiReturn_SUCCESS;
+
+            let ok = ffi::aiGetMaterialString(
+                self.as_ptr(), key.as_ptr() as *const c_char, 0, 0, &mut ret
+            ) == aiReturn_SUCCESS;
+            if !ok {
+                return None;
+            }
+        }
+        prim::str(&ret).map(|s| s.to_owned())
+    }
+
+    /// Reads a single float property. `key` must be NUL-terminated.
+    fn get_float(&self, key: &str) -> Option<f32> {
+        let mut ret = 0.0;
+        unsafe {
+            use ffi::aiReturn::aiReturn_SUCCESS;
+
+            let ok = ffi::aiGetMaterialFloatArray(
+                self.as_ptr(), key.as_ptr() as *const c_char, 0, 0, &mut ret, ptr::null_mut()
+            ) == aiReturn_SUCCESS;
+            if !ok {
+                return None;
+            }
+        }
+        Some(ret)
+    }
+
+    /// Reads a single float property of the `idx`-th texture of type
+    /// `tex_ty`. `key` must be NUL-terminated.
+    fn get_texture_float(&self, key: &str, tex_ty: TextureType, idx: u32) -> Option<f32> {
+        let mut ret = 0.0;
+        unsafe {
+            use ffi::aiReturn::aiReturn_SUCCESS;
+
+            let ok = ffi::aiGetMaterialFloatArray(
+                self.as_ptr(), key.as_ptr() as *const c_char, tex_ty as c_uint, idx, &mut ret, ptr::null_mut()
+            ) == aiReturn_SUCCESS;
+            if !ok {
+                return None;
+            }
+        }
+        Some(ret)
+    }
+
+    fn textures(&self, tex_ty: TextureType) -> Vec<TextureProperties> {
+        self.indexed_textures(tex_ty).into_iter().map(|(_, tex)| tex).collect()
+    }
+
+    /// The textures of a slot with their index in the texture stack.
+    fn indexed_textures(&self, tex_ty: TextureType) -> Vec<(u32, TextureProperties)> {
+        (0..self.count_texture_properties(tex_ty))
+            .filter_map(|idx| self.texture_properties(tex_ty, idx).map(|tex| (idx, tex)))
+            .collect()
+    }
+}
+
+// ++++++++++++++++++++ MaterialBinding ++++++++++++++++++++
+
+/// Maps material names to application defined materials.
+///
+/// Rules are glob patterns (`*` and `?`) matched against the material
+/// name, the first matching rule wins.
+#[derive(Debug, Clone)]
+pub struct MaterialBinding<T> {
+    rules: Vec<(String, T)>,
+    fallback: Option<T>,
 }
 
+/// The result of `MaterialBinding::resolve()`.
+#[derive(Debug, Clone)]
+pub struct ResolvedBindings<T> {
+    /// The target for each mesh of the scene, by mesh index.
+    pub meshes: Vec<Option<T>>,
+    /// The target for each material of the scene, by material index.
+    pub materials: Vec<Option<T>>,
+    /// Names of the materials no rule matched (ignoring the fallback).
+    pub unmatched: Vec<String>,
+}
+
+impl<T: Clone> MaterialBinding<T> {
+    pub fn new() -> Self {
+        MaterialBinding { rules: Vec::new(), fallback: None }
+    }
+
+    /// Binds all materials whose name matches `pattern` to `target`.
+    pub fn bind(&mut self, pattern: &str, target: T) -> &mut Self {
+        self.rules.push((pattern.to_owned(), target));
+        self
+    }
+
+    /// The target used for materials no rule matches.
+    pub fn fallback(&mut self, target: T) -> &mut Self {
+        self.fallback = Some(target);
+        self
+    }
+
+    /// The target for a single material name.
+    pub fn lookup(&self, name: &str) -> Option<&T> {
+        self.rules.iter().find(|r| prim::glob_match(&r.0, name)).map(|r| &r.1)
+    }
+
+    /// Resolves the materials of all meshes in the scene.
+    pub fn resolve(&self, scene: &Scene) -> ResolvedBindings<T> {
+        let mut unmatched = Vec::new();
+        let materials: Vec<Option<T>> = scene
+            .materials()
+            .iter()
+            .map(|mat| {
+                let name = mat.material_properties().name;
+                match self.lookup(&name) {
+                    Some(target) => Some(target.clone()),
+                    None => {
+                        unmatched.push(name);
+                        self.fallback.clone()
+                    }
+                }
+            })
+            .collect();
+        let meshes = scene
+            .meshes()
+            .iter()
+            .map(|mesh| materials.get(mesh.material().idx()).and_then(|t| t.clone()))
+            .collect();
+        ResolvedBindings { meshes, materials, unmatched }
+    }
+}
+
+impl<T: Clone> Default for MaterialBinding<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ++++++++++++++++++++ MaterialTable ++++++++++++++++++++
+
+/// A block of material constants in a `MaterialTable`.
+///
+/// Every field occupies 16 bytes (one `vec4`/`ivec4`), so the table can be
+/// read with both std140 and std430 layout rules.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MaterialField {
+    /// Diffuse color, opacity as alpha (`vec4`).
+    BaseColor,
+    /// Normalized emissive color and strength (`vec4`), see
+    /// `PhysicalProperties`.
+    Emissive,
+    /// Specular color and shininess (`vec4`).
+    Specular,
+    /// Index of refraction, f0, transmission and alpha cutoff (`vec4`).
+    /// The cutoff is negative unless the alpha mode is `AlphaMode::Mask`.
+    Factors,
+    /// Indices of the diffuse, normal, emissive and occlusion textures
+    /// (`ivec4`), -1 if absent.
+    ///
+    /// Indices refer to the entries of `Scene::texture_usage()`, which
+    /// makes a natural order for a scene wide texture array.
+    TextureIndices,
+}
+
+/// Material constants of a whole scene packed into one buffer, see
+/// `Scene::material_table()`.
+#[derive(Debug, Clone)]
+pub struct MaterialTable {
+    /// The packed constants, native byte order, one record per material.
+    pub bytes: Vec<u8>,
+    /// The size of a record in bytes.
+    pub stride: usize,
+    /// The fields of a record with their byte offsets.
+    pub fields: Vec<(MaterialField, usize)>,
+}
+
+impl MaterialTable {
+    pub fn build(scene: &Scene, fields: &[MaterialField]) -> MaterialTable {
+        let textures: Vec<TextureRef> = scene.texture_usage().into_iter().map(|u| u.texture).collect();
+        let texture_idx = |props: Option<&TextureProperties>| -> i32 {
+            let props = match props {
+                Some(props) => props,
+                None => return -1,
+            };
+            let texture = TextureRef::parse(&props.texture_ref);
+            textures.iter().position(|t| *t == texture).map_or(-1, |idx| idx as i32)
+        };
+
+        let stride = fields.len() * 16;
+        let mut bytes = Vec::with_capacity(stride * scene.materials().len());
+        for material in scene.materials() {
+            let props = material.material_properties();
+            let physical = props.to_physical();
+            for field in fields {
+                match *field {
+                    MaterialField::BaseColor => push_floats(&mut bytes, physical.base_color),
+                    MaterialField::Emissive => {
+                        let [r, g, b] = physical.emissive_color;
+                        push_floats(&mut bytes, [r, g, b, physical.emissive_strength]);
+                    }
+                    MaterialField::Specular => {
+                        let [r, g, b, _] = props.color_specular;
+                        push_floats(&mut bytes, [r, g, b, props.shininess]);
+                    }
+                    MaterialField::Factors => {
+                        let cutoff = match material.alpha_mode() {
+                            AlphaMode::Mask { cutoff } => cutoff,
+                            _ => -1.0,
+                        };
+                        push_floats(&mut bytes, [physical.ior, physical.f0, physical.transmission, cutoff]);
+                    }
+                    MaterialField::TextureIndices => {
+                        // the textures occlusion() and bump_maps() picked, not
+                        // the first of their slot
+                        let occlusion = material.occlusion().map(|o| o.texture);
+                        let normals = material.bump_maps().into_iter().find(|b| b.kind == BumpMapKind::Normals).map(|b| b.texture);
+                        let indices = [
+                            texture_idx(material.texture_properties(TextureType::Diffuse, 0).as_ref()),
+                            texture_idx(normals.as_ref()),
+                            texture_idx(material.texture_properties(TextureType::Emissive, 0).as_ref()),
+                            texture_idx(occlusion.as_ref()),
+                        ];
+                        for idx in &indices {
+                            bytes.extend_from_slice(&idx.to_ne_bytes());
+                        }
+                    }
+                }
+            }
+        }
+
+        MaterialTable {
+            bytes,
+            stride,
+            fields: fields.iter().enumerate().map(|(idx, &field)| (field, idx * 16)).collect(),
+        }
+    }
+}
+
+fn push_floats(bytes: &mut Vec<u8>, values: [f32; 4]) {
+    for v in &values {
+        bytes.extend_from_slice(&v.to_bits().to_ne_bytes());
+    }
+}
+
+// ++++++++++++++++++++ BumpMap ++++++++++++++++++++
+
+/// What kind of surface detail a texture encodes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BumpMapKind {
+    /// Grayscale heights, higher values are further outwards.
+    Height,
+
+    /// Tangent space normals.
+    Normals,
+}
+
+/// A texture affecting surface detail, see `Material::bump_maps()`.
+#[derive(Debug, Clone)]
+pub struct BumpMap {
+    /// The texture slot the texture was found in.
+    pub source: TextureType,
+    /// The index of the texture in the `source` slot's texture stack.
+    pub idx: u32,
+    /// What the texture most likely contains.
+    pub kind: BumpMapKind,
+    pub texture: TextureProperties,
+}
+
+// ++++++++++++++++++++ Occlusion ++++++++++++++++++++
+
+/// The channels of a texture holding a value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureChannel {
+    R,
+    G,
+    B,
+    A,
+    /// All color channels, e.g. a colored lightmap.
+    Rgb,
+}
+
+/// An ambient occlusion texture, see `Material::occlusion()`.
+#[derive(Debug, Clone)]
+pub struct Occlusion {
+    /// The texture slot the texture was found in.
+    pub source: TextureType,
+    /// The index of the texture in the `source` slot's texture stack.
+    pub idx: u32,
+    pub texture: TextureProperties,
+    /// The channel(s) holding the occlusion term.
+    pub channel: TextureChannel,
+    /// How strongly occlusion is applied, from 0 (none) to 1 (full).
+    pub strength: f32,
+}
+
+const OCCLUSION_MAP_HINTS: [&'static str; 6] = ["occlusion", "_ao", "_orm", "_arm", "_rma", "_occ"];
+
+/// Hints only count at the end of the stem, `left_arm_diffuse.png` or
+/// `soccer_ball.png` aren't occlusion maps.
+fn is_occlusion_map_name(path: &str) -> bool {
+    let stem = file_name(strip_extension(path)).to_lowercase();
+    stem == "ao" || OCCLUSION_MAP_HINTS.iter().any(|h| stem.ends_with(h))
+}
+
+const NORMAL_MAP_HINTS: [&'static str; 4] = ["normal", "_nrm", "_norm", "_n"];
+
+fn is_normal_map_name(path: &str) -> bool {
+    let stem = file_name(strip_extension(path)).to_lowercase();
+    NORMAL_MAP_HINTS.iter().any(|h| if h.starts_with('_') { stem.ends_with(h) } else { stem.contains(h) })
+}
+
+// ++++++++++++++++++++ EnvironmentMap ++++++++++++++++++++
+
+/// An environment (image based lighting) map referenced by a material.
+///
+/// See `Material::environment_maps()`.
+#[derive(Debug, Clone)]
+pub enum EnvironmentMap {
+    /// A cube map given as six separate textures.
+    ///
+    /// The faces are ordered +X, -X, +Y, -Y, +Z, -Z.
+    CubeFaces([String; 6]),
+
+    /// A cube map stored in a single texture (e.g. a DDS cube map).
+    Cube(String),
+
+    /// A latitude-longitude panorama.
+    Equirectangular(String),
+
+    /// A reflection texture without any hint about its projection.
+    Other(String),
+}
+
+/// Face name suffixes, each in the order +X, -X, +Y, -Y, +Z, -Z.
+const CUBE_FACE_SUFFIXES: [[&'static str; 6]; 4] = [
+    ["posx", "negx", "posy", "negy", "posz", "negz"],
+    ["px", "nx", "py", "ny", "pz", "nz"],
+    ["right", "left", "top", "bottom", "front", "back"],
+    ["rt", "lf", "up", "dn", "ft", "bk"],
+];
+
+const EQUIRECT_HINTS: [&'static str; 4] = ["equirect", "latlong", "latlon", "panorama"];
+
+/// Splits a texture path like `sky_posx.png` into (`sky`, convention, face).
+fn cube_face(path: &str) -> Option<(String, usize, usize)> {
+    let stem = strip_extension(path).to_lowercase();
+
+    for (convention, suffixes) in CUBE_FACE_SUFFIXES.iter().enumerate() {
+        for (face, suffix) in suffixes.iter().enumerate() {
+            if !stem.ends_with(suffix) {
+                continue;
+            }
+            let prefix = &stem[..stem.len() - suffix.len()];
+            if prefix.ends_with(|c| c == '_' || c == '-' || c == '.') {
+                return Some((prefix[..prefix.len() - 1].to_owned(), convention, face));
+            }
+        }
+    }
+    None
+}
+
+fn file_name(path: &str) -> &str {
+    match path.rfind(|c| c == '/' || c == '\\') {
+        Some(idx) => &path[idx + 1..],
+        None => path,
+    }
+}
+
+fn strip_extension(path: &str) -> &str {
+    let name_start = path.len() - file_name(path).len();
+    match path[name_start..].rfind('.') {
+        Some(idx) => &path[..name_start + idx],
+        None => path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn occlusion_map_names() {
+        assert!(is_occlusion_map_name("rock_ao.png"));
+        assert!(is_occlusion_map_name("textures/AO.tga"));
+        assert!(is_occlusion_map_name("Helmet_ORM.jpg"));
+        assert!(is_occlusion_map_name("wall_occlusion.png"));
+        assert!(!is_occlusion_map_name("left_arm_diffuse.png"));
+        assert!(!is_occlusion_map_name("soccer_ball.png"));
+        assert!(!is_occlusion_map_name("chaos_albedo.png"));
+        assert!(!is_occlusion_map_name("ao/diffuse.png"));
+    }
+
+    #[test]
+    fn normal_map_names() {
+        assert!(is_normal_map_name("rock_n.png"));
+        assert!(is_normal_map_name("Rock_Normal.dds"));
+        assert!(is_normal_map_name("rock_nrm.tga"));
+        assert!(!is_normal_map_name("rock_diffuse.png"));
+        assert!(!is_normal_map_name("n/rock.png"));
+    }
+
+    fn assert_face(dir: Vector3, face: CubeFace, uv: Vector2) {
+        let (f, [u, v]) = CubeFace::from_direction(dir);
+        assert_eq!(f, face, "{:?}", dir);
+        assert!((u - uv[0]).abs() < 1e-6 && (v - uv[1]).abs() < 1e-6, "{:?}: {:?}", dir, [u, v]);
+    }
+
+    #[test]
+    fn cube_face_centers() {
+        assert_face([1.0, 0.0, 0.0], CubeFace::PositiveX, [0.5, 0.5]);
+        assert_face([-2.0, 0.0, 0.0], CubeFace::NegativeX, [0.5, 0.5]);
+        assert_face([0.0, 1.0, 0.0], CubeFace::PositiveY, [0.5, 0.5]);
+        assert_face([0.0, -1.0, 0.0], CubeFace::NegativeY, [0.5, 0.5]);
+        assert_face([0.0, 0.0, 3.0], CubeFace::PositiveZ, [0.5, 0.5]);
+        assert_face([0.0, 0.0, -1.0], CubeFace::NegativeZ, [0.5, 0.5]);
+        assert_face([0.0, 0.0, 0.0], CubeFace::PositiveX, [0.5, 0.5]);
+    }
+
+    #[test]
+    fn cube_face_corners() {
+        // OpenGL: +X has s along -z and t along -y
+        assert_face([1.0, 1.0, 0.5], CubeFace::PositiveX, [0.25, 0.0]);
+        assert_face([-1.0, -0.5, 1.0], CubeFace::NegativeX, [1.0, 0.75]);
+        assert_face([0.5, 1.0, -1.0], CubeFace::PositiveY, [0.75, 0.0]);
+        assert_face([0.5, -1.0, -1.0], CubeFace::NegativeY, [0.75, 1.0]);
+        assert_face([-0.5, 0.5, 1.0], CubeFace::PositiveZ, [0.25, 0.25]);
+        assert_face([-0.5, 0.5, -1.0], CubeFace::NegativeZ, [0.75, 0.25]);
+    }
+}
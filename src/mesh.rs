@@ -1,10 +1,26 @@
-use prim::{self, Color4, Matrix4, Vector3};
+use prim::{self, Aabb, BoneOffsetMatrix, Color4, Matrix4, Vector3};
 use ffi;
 use libc::c_uint;
 
 pub type VertexIdx = c_uint;
+
+/// Index into `Scene::materials()`.
+#[deprecated(note = "use `MaterialHandle`")]
 pub type MaterialIdx = c_uint;
 
+/// Refers to a material of a scene, see `Scene::material()`.
+///
+/// The wrapped value is the index into `Scene::materials()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MaterialHandle(pub u32);
+
+impl MaterialHandle {
+    /// The index into `Scene::materials()`.
+    pub fn idx(&self) -> usize {
+        self.0 as usize
+    }
+}
+
 // ++++++++++++++++++++ Face ++++++++++++++++++++
 
 ai_type!{
@@ -87,6 +103,11 @@ impl<'a> Bone<'a> {
     pub fn offset_matrix(&self) -> Matrix4 {
         prim::mat4(self.raw().mOffsetMatrix)
     }
+
+    /// `offset_matrix()`, tagged with its coordinate space.
+    pub fn offset(&self) -> BoneOffsetMatrix {
+        BoneOffsetMatrix(self.offset_matrix())
+    }
 }
 
 // ++++++++++++++++++++ PrimitiveTypes ++++++++++++++++++++
@@ -130,41 +151,117 @@ bitflags!{
 ai_impl_enum!(PrimitiveTypes, c_uint);
 
 // ++++++++++++++++++++ AnimMesh ++++++++++++++++++++
-//
-// TODO (not currently in use?)
-//
-// pub struct AnimMesh<'a> {
-// raw: &'a ffi::aiAnimMesh
-// }
-//
-// impl<'a> AnimMesh<'a> {
-// pub fn num_vertices(&self) -> usize {
-// self.raw.mNumVertices as usize
-// }
-//
-// pub fn vertices(&self) -> &[Vector3] {
-// unsafe {
-// prim::vec3_slice(self.raw.mVertices, self.num_vertices())
-// }
-// }
-// pub fn normals(&self) -> &[Vector3] {
-// unsafe {
-// prim::vec3_slice(self.raw.mNormals, self.num_vertices())
-// }
-// }
-// pub fn tangents(&self) -> &[Vector3] {
-// unsafe {
-// prim::vec3_slice(self.raw.mTangents, self.num_vertices())
-// }
-// }
-// pub fn bitangents(&self) -> &[Vector3] {
-// unsafe {
-// prim::vec3_slice(self.raw.mBitangents, self.num_vertices())
-// }
-// }
-//
-// TODO colors, coords
-// }
+
+ai_ptr_type!{
+    /// An AnimMesh is an attachment to a Mesh and stores per-vertex
+    /// animations for a particular frame.
+    ///
+    /// You may think of an AnimMesh as a 'patch' for the host mesh, which
+    /// replaces only certain vertex data streams at a particular time.
+    /// Each mesh stores n attached meshes (#aiMesh::mAnimMeshes).
+    /// The actual relationship between the time line and anim meshes is
+    /// established by #aiMeshAnim, which references singular mesh attachments
+    /// by their ID and binds them to a time offset.
+    type AnimMesh: ffi::aiAnimMesh;
+}
+
+impl<'a> AnimMesh<'a> {
+    /// Replacement for Mesh::vertices(). If this array is empty,
+    /// the vertex positions are not replaced.
+    pub fn vertices(&self) -> &[Vector3] {
+        unsafe { prim::slice(self.raw().mVertices, self.raw().mNumVertices) }
+    }
+
+    /// Replacement for Mesh::normals().
+    pub fn normals(&self) -> &[Vector3] {
+        unsafe { prim::slice(self.raw().mNormals, self.raw().mNumVertices) }
+    }
+
+    /// Replacement for Mesh::tangents().
+    pub fn tangents(&self) -> &[Vector3] {
+        unsafe { prim::slice(self.raw().mTangents, self.raw().mNumVertices) }
+    }
+
+    /// Replacement for Mesh::bitangents().
+    pub fn bitangents(&self) -> &[Vector3] {
+        unsafe { prim::slice(self.raw().mBitangents, self.raw().mNumVertices) }
+    }
+
+    /// Replacement for Mesh::colors().
+    pub fn colors(&self, channel: usize) -> &[Color4] {
+        if channel >= ffi::AI_MAX_NUMBER_OF_COLOR_SETS {
+            return &[];
+        }
+        unsafe { prim::slice(self.raw().mColors[channel], self.raw().mNumVertices) }
+    }
+
+    /// Replacement for Mesh::texture_coords().
+    pub fn texture_coords(&self, channel: usize) -> &[Vector3] {
+        if channel >= ffi::AI_MAX_NUMBER_OF_TEXTURECOORDS {
+            return &[];
+        }
+        unsafe { prim::slice(self.raw().mTextureCoords[channel], self.raw().mNumVertices) }
+    }
+
+    /// Position offsets relative to the host mesh.
+    ///
+    /// Empty if this anim mesh doesn't replace positions or doesn't match
+    /// the vertex count of `base`.
+    pub fn position_deltas(&self, base: &Mesh) -> Vec<Vector3> {
+        deltas(self.vertices(), base.vertices())
+    }
+
+    /// Normal offsets relative to the host mesh, see `position_deltas()`.
+    pub fn normal_deltas(&self, base: &Mesh) -> Vec<Vector3> {
+        deltas(self.normals(), base.normals())
+    }
+
+    /// Tangent offsets relative to the host mesh, see `position_deltas()`.
+    pub fn tangent_deltas(&self, base: &Mesh) -> Vec<Vector3> {
+        deltas(self.tangents(), base.tangents())
+    }
+}
+
+fn deltas(target: &[Vector3], base: &[Vector3]) -> Vec<Vector3> {
+    if target.len() != base.len() {
+        return Vec::new();
+    }
+    target.iter().zip(base).map(|(&t, &b)| prim::sub(t, b)).collect()
+}
+
+/// Per-vertex offsets which omit vertices that don't move.
+#[derive(Debug, Clone, Default)]
+pub struct SparseDeltas {
+    /// Indices of the affected vertices, ascending.
+    pub indices: Vec<VertexIdx>,
+    /// The offset of each vertex in `indices`.
+    pub deltas: Vec<Vector3>,
+}
+
+impl SparseDeltas {
+    /// Drops all offsets whose components are all within `epsilon` of zero.
+    pub fn from_dense(deltas: &[Vector3], epsilon: f32) -> Self {
+        let mut ret = SparseDeltas::default();
+        for (idx, d) in deltas.iter().enumerate() {
+            if d.iter().any(|c| c.abs() > epsilon) {
+                ret.indices.push(idx as VertexIdx);
+                ret.deltas.push(*d);
+            }
+        }
+        ret
+    }
+
+    /// Expands the offsets back into one entry per vertex.
+    pub fn to_dense(&self, num_vertices: usize) -> Vec<Vector3> {
+        let mut ret = vec![[0.0; 3]; num_vertices];
+        for (&idx, d) in self.indices.iter().zip(&self.deltas) {
+            if let Some(slot) = ret.get_mut(idx as usize) {
+                *slot = *d;
+            }
+        }
+        ret
+    }
+}
 
 // ++++++++++++++++++++ Mesh ++++++++++++++++++++
 
@@ -328,18 +425,277 @@ impl<'a> Mesh<'a> {
     ///
     /// A bone consists of a name by which it can be found in the
     /// frame hierarchy and a set of vertex weights.
-    pub fn bones(&self) -> &[Bone] {
+    pub fn bones(&self) -> &[Bone<'_>] {
         unsafe { Bone::slice(self.raw().mBones, self.raw().mNumBones) }
     }
 
     /// The material used by this mesh.
     ///
     /// A mesh uses only a single material. If an imported model uses
-    /// multiple materials, the import splits up the mesh. Use this value
-    /// as index into the scene's material list.
+    /// multiple materials, the import splits up the mesh. Look the
+    /// material up with `Scene::material()`.
+    pub fn material(&self) -> MaterialHandle {
+        MaterialHandle(self.raw().mMaterialIndex)
+    }
+
+    /// Same as `material()`, as a plain index.
+    #[deprecated(note = "use `Mesh::material()`, which returns a `MaterialHandle`")]
+    #[allow(deprecated)]
     pub fn material_idx(&self) -> MaterialIdx {
-        self.raw().mMaterialIndex
+        self.material().0
+    }
+
+    /// The bounding box of the vertex positions, `None` for meshes without
+    /// vertices.
+    pub fn aabb(&self) -> Option<Aabb> {
+        Aabb::from_points(self.vertices().iter().cloned())
+    }
+
+    /// The total area of all triangles and polygons.
+    ///
+    /// Polygons are assumed to be planar and convex.
+    pub fn surface_area(&self) -> f32 {
+        self.triangles()
+            .map(|[a, b, c]| prim::length(prim::cross(prim::sub(b, a), prim::sub(c, a))) * 0.5)
+            .sum()
+    }
+
+    /// The enclosed volume.
+    ///
+    /// Only meaningful for closed, consistently wound meshes. The absolute
+    /// value is returned, so inverted winding doesn't matter.
+    pub fn volume(&self) -> f32 {
+        let signed: f32 = self.triangles()
+            .map(|[a, b, c]| prim::dot(a, prim::cross(b, c)) / 6.0)
+            .sum();
+        signed.abs()
+    }
+
+    /// Checks whether this mesh provides all vertex streams required by
+    /// `req`.
+    pub fn satisfies(&self, req: &VertexLayoutRequirement) -> Result<(), MissingStreams> {
+        let missing = MissingStreams {
+            normals: req.normals && self.normals().is_empty(),
+            tangents: req.tangents && (self.tangents().is_empty() || self.bitangents().is_empty()),
+            uv_channels: (0..req.uv_channels).filter(|&c| self.texture_coords(c).is_empty()).collect(),
+            color_channels: (0..req.color_channels).filter(|&c| self.colors(c).is_empty()).collect(),
+            bones: req.bones && self.bones().is_empty(),
+        };
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing)
+        }
+    }
+
+    /// All attributes of a single vertex, `None` if `idx` is out of range.
+    pub fn vertex(&self, idx: VertexIdx) -> Option<Vertex> {
+        let idx = idx as usize;
+        let position = *self.vertices().get(idx)?;
+        let mut texture_coords = [None; ::MAX_TEXTURE_COORDS];
+        for (channel, uv) in texture_coords.iter_mut().enumerate() {
+            *uv = self.texture_coords(channel).get(idx).cloned();
+        }
+        let mut colors = [None; ::MAX_COLOR_SETS];
+        for (channel, color) in colors.iter_mut().enumerate() {
+            *color = self.colors(channel).get(idx).cloned();
+        }
+        Some(Vertex {
+            position,
+            normal: self.normals().get(idx).cloned(),
+            tangent: self.tangents().get(idx).cloned(),
+            bitangent: self.bitangents().get(idx).cloned(),
+            texture_coords,
+            colors,
+        })
+    }
+
+    /// All triangles as triangle soup, with polygons split into fans.
+    ///
+    /// Works the same for indexed and verbose (non-indexed) meshes, points
+    /// and lines are skipped, as are triangles with out-of-range indices.
+    pub fn deindexed_triangles<'b>(&'b self) -> impl Iterator<Item = [Vertex; 3]> + 'b {
+        self.triangle_indices().filter_map(move |[a, b, c]| Some([self.vertex(a)?, self.vertex(b)?, self.vertex(c)?]))
+    }
+
+    /// Vertex positions of all triangles, with polygons split into fans.
+    pub fn triangles<'b>(&'b self) -> impl Iterator<Item = [Vector3; 3]> + 'b {
+        let vertices = self.vertices();
+        self.triangle_indices().map(move |[a, b, c]| {
+            [vertices[a as usize], vertices[b as usize], vertices[c as usize]]
+        })
+    }
+
+    /// Vertex indices of all triangles, with polygons split into fans.
+    fn triangle_indices<'b>(&'b self) -> impl Iterator<Item = [VertexIdx; 3]> + 'b {
+        self.faces().iter().flat_map(|face| {
+            let indices = face.indices();
+            let fan = if indices.len() >= 3 { indices.len() - 2 } else { 0 };
+            (0..fan).map(move |i| [indices[0], indices[i + 1], indices[i + 2]])
+        })
+    }
+
+    /// Per-vertex colors visualizing the influence of `self.bones()[bone]`,
+    /// for debugging skinning.
+    ///
+    /// Weights are mapped from blue (no influence) over green to red (full
+    /// influence). The result can be used as a vertex color channel by any
+    /// viewer. `None` if the mesh has no bone `bone`.
+    pub fn weight_heatmap(&self, bone: usize) -> Option<Vec<Color4>> {
+        let bone = self.bones().get(bone)?;
+        let mut weights = vec![0.0f32; self.vertices().len()];
+        for w in bone.weights() {
+            if let Some(weight) = weights.get_mut(w.vertex_idx() as usize) {
+                *weight += w.weight();
+            }
+        }
+        let colors = weights
+            .into_iter()
+            .map(|w| {
+                let w = w.max(0.0).min(1.0);
+                if w < 0.5 {
+                    [0.0, w * 2.0, 1.0 - w * 2.0, 1.0]
+                } else {
+                    [w * 2.0 - 1.0, 2.0 - w * 2.0, 0.0, 1.0]
+                }
+            })
+            .collect();
+        Some(colors)
+    }
+
+    /// Views a mesh consisting only of points (as imported from PLY or
+    /// other scan formats) as a point cloud, `None` for meshes with lines
+    /// or polygons.
+    pub fn as_point_cloud(&self) -> Option<PointCloud<'_>> {
+        if self.primitive_types() != POINT {
+            return None;
+        }
+        Some(PointCloud {
+            positions: self.vertices(),
+            normals: Some(self.normals()).filter(|n| !n.is_empty()),
+            colors: Some(self.colors(0)).filter(|c| !c.is_empty()),
+            attributes: (0..::MAX_TEXTURE_COORDS)
+                .map(|channel| self.texture_coords(channel))
+                .filter(|channel| !channel.is_empty())
+                .collect(),
+        })
+    }
+
+    /// Attachment meshes for this mesh, for vertex-based animation.
+    ///
+    /// Attachment meshes carry replacement data for some of the
+    /// mesh'es vertex components (usually positions, normals).
+    pub fn anim_meshes(&self) -> &[AnimMesh<'_>] {
+        unsafe { AnimMesh::slice(self.raw().mAnimMeshes, self.raw().mNumAnimMeshes) }
+    }
+}
+
+/// All attributes of a single vertex, see `Mesh::vertex()`.
+///
+/// Streams the mesh doesn't have are `None`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vertex {
+    pub position: Vector3,
+    pub normal: Option<Vector3>,
+    pub tangent: Option<Vector3>,
+    pub bitangent: Option<Vector3>,
+    pub texture_coords: [Option<Vector3>; ::MAX_TEXTURE_COORDS],
+    pub colors: [Option<Color4>; ::MAX_COLOR_SETS],
+}
+
+// ++++++++++++++++++++ VertexLayoutRequirement ++++++++++++++++++++
+
+/// The vertex streams a shader expects, see `Mesh::satisfies()`.
+///
+/// Positions are always required and therefore not listed.
+#[derive(Debug, Clone, Default)]
+pub struct VertexLayoutRequirement {
+    /// Requires vertex normals.
+    pub normals: bool,
+    /// Requires tangents and bitangents.
+    pub tangents: bool,
+    /// Requires the texture coordinate channels `0..uv_channels`.
+    pub uv_channels: usize,
+    /// Requires the vertex color channels `0..color_channels`.
+    pub color_channels: usize,
+    /// Requires bone weights (joints).
+    pub bones: bool,
+}
+
+/// The vertex streams a mesh lacks to satisfy a `VertexLayoutRequirement`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingStreams {
+    pub normals: bool,
+    pub tangents: bool,
+    /// Indices of the missing texture coordinate channels.
+    pub uv_channels: Vec<usize>,
+    /// Indices of the missing vertex color channels.
+    pub color_channels: Vec<usize>,
+    pub bones: bool,
+}
+
+impl MissingStreams {
+    pub fn is_empty(&self) -> bool {
+        !self.normals && !self.tangents && self.uv_channels.is_empty() && self.color_channels.is_empty()
+            && !self.bones
+    }
+}
+
+// ++++++++++++++++++++ PointCloud ++++++++++++++++++++
+
+/// A mesh consisting only of points, see `Mesh::as_point_cloud()`.
+///
+/// All streams are indexed by point.
+#[derive(Debug, Clone)]
+pub struct PointCloud<'a> {
+    pub positions: &'a [Vector3],
+    pub normals: Option<&'a [Vector3]>,
+    /// The first vertex color channel.
+    pub colors: Option<&'a [Color4]>,
+    /// Extra per-point attributes (e.g. intensity or confidence), which
+    /// importers store in texture coordinate channels.
+    pub attributes: Vec<&'a [Vector3]>,
+}
+
+impl<'a> PointCloud<'a> {
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn aabb(&self) -> Option<Aabb> {
+        Aabb::from_points(self.positions.iter().cloned())
     }
 
-    // TODO anim meshes (currently not in use?)
+    /// The mean position of all points.
+    pub fn centroid(&self) -> Option<Vector3> {
+        if self.positions.is_empty() {
+            return None;
+        }
+        let mut sum = [0.0f64; 3];
+        for p in self.positions {
+            for i in 0..3 {
+                sum[i] += p[i] as f64;
+            }
+        }
+        let n = self.positions.len() as f64;
+        Some([(sum[0] / n) as f32, (sum[1] / n) as f32, (sum[2] / n) as f32])
+    }
+
+    /// Points per cubic unit of the bounding box.
+    ///
+    /// Flat axes (e.g. of a planar scan) are ignored, so this is the
+    /// density per area or length for 2D and 1D clouds. `None` if there
+    /// are less than two distinct points.
+    pub fn density(&self) -> Option<f32> {
+        let size = self.aabb()?.size();
+        if size.iter().all(|&s| s <= 0.0) {
+            return None;
+        }
+        let extent: f32 = size.iter().filter(|&&s| s > 0.0).product();
+        Some(self.len() as f32 / extent)
+    }
 }
@@ -19,10 +19,10 @@ ai_ptr_type!{
 }
 
 impl<'a> MetaData<'a> {
-    pub fn iter(&self) -> Iter {
+    pub fn iter(&self) -> Iter<'a> {
         Iter::new(self.raw())
     }
-    pub fn get(&self, key: &str) -> Option<MetadataValue> {
+    pub fn get(&self, key: &str) -> Option<MetadataValue<'a>> {
         self.iter().find(|&(k, _)| k == key).map(|(_, v)| v)
     }
 }
@@ -47,13 +47,14 @@ impl<'a> Iterator for Iter<'a> {
         if self.idx >= self.raw.mNumProperties as usize {
             return None;
         }
+        let idx = self.idx;
         self.idx += 1;
 
         unsafe {
             use ffi::aiMetadataType::*;
 
-            let key = prim::str(&*self.raw.mKeys.offset(self.idx as isize)).unwrap();
-            let val_ptr = self.raw.mValues.offset(self.idx as isize);
+            let key = prim::str(&*self.raw.mKeys.offset(idx as isize)).unwrap();
+            let val_ptr = self.raw.mValues.offset(idx as isize);
             if val_ptr.is_null() {
                 return self.next();
             }
@@ -71,3 +72,56 @@ impl<'a> Iterator for Iter<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ai_string(s: &str) -> ffi::aiString {
+        let mut ret: ffi::aiString = unsafe { ::std::mem::zeroed() };
+        for (dst, &src) in ret.data.iter_mut().zip(s.as_bytes()) {
+            *dst = src as ::libc::c_char;
+        }
+        ret.length = s.len() as _;
+        ret
+    }
+
+    #[test]
+    fn iter_returns_every_entry() {
+        let mut keys = [ai_string("UpAxis"), ai_string("UnitScaleFactor"), ai_string("Author")];
+        let (mut up, mut scale, mut author) = (1i32, 100.0f32, ai_string("me"));
+        let mut values = [
+            ffi::aiMetadataEntry { mType: ffi::aiMetadataType::AI_INT, mData: &mut up as *mut i32 as *mut _ },
+            ffi::aiMetadataEntry { mType: ffi::aiMetadataType::AI_FLOAT, mData: &mut scale as *mut f32 as *mut _ },
+            ffi::aiMetadataEntry { mType: ffi::aiMetadataType::AI_AISTRING, mData: &mut author as *mut _ as *mut _ },
+        ];
+        let mut raw = ffi::aiMetadata { mNumProperties: 3, mKeys: keys.as_mut_ptr(), mValues: values.as_mut_ptr() };
+        let meta = unsafe { MetaData::from_ptr(&mut raw) };
+
+        let entries: Vec<_> = meta.iter().collect();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].0, "UpAxis");
+        assert_eq!(entries[2].0, "Author");
+        match meta.get("UpAxis") {
+            Some(MetadataValue::I32(1)) => {}
+            other => panic!("{:?}", other),
+        }
+        match meta.get("UnitScaleFactor") {
+            Some(MetadataValue::F32(v)) => assert_eq!(v, 100.0),
+            other => panic!("{:?}", other),
+        }
+        match meta.get("Author") {
+            Some(MetadataValue::String("me")) => {}
+            other => panic!("{:?}", other),
+        }
+        assert!(meta.get("Missing").is_none());
+    }
+
+    #[test]
+    fn iter_empty() {
+        let mut keys: [ffi::aiString; 0] = [];
+        let mut values: [ffi::aiMetadataEntry; 0] = [];
+        let mut raw = ffi::aiMetadata { mNumProperties: 0, mKeys: keys.as_mut_ptr(), mValues: values.as_mut_ptr() };
+        assert_eq!(unsafe { MetaData::from_ptr(&mut raw) }.iter().count(), 0);
+    }
+}
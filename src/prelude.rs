@@ -0,0 +1,19 @@
+//! The types needed by most users, for glob importing:
+//!
+//! ```ignore
+//! use assimp_import::prelude::*;
+//! ```
+//!
+//! Everything else lives in the module it belongs to, e.g.
+//! `assimp_import::skeleton::Skeleton`.
+
+pub use anim::Animation;
+pub use camera::Camera;
+pub use config::PropertyStore;
+pub use data::SceneData;
+pub use light::Light;
+pub use material::{Material, TextureType};
+pub use mesh::{MaterialHandle, Mesh};
+pub use postprocess::PostProcessSteps;
+pub use scene::{MeshHandle, Node, NodeHandle, Scene};
+pub use texture::Texture;
@@ -1,5 +1,7 @@
 use ffi;
 use std::{mem, slice, str};
+use std::ffi::CString;
+use std::path::Path;
 use libc::c_uint;
 
 /// x, y
@@ -22,6 +24,40 @@ pub type Matrix4 = [[f32; 4]; 4];
 /// w, x, y, z
 pub type Quaternion = [f32; 4];
 
+/// A transformation relative to the parent node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LocalMatrix(pub Matrix4);
+
+/// A transformation relative to the root node of the scene.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldMatrix(pub Matrix4);
+
+/// A transformation from mesh space to bone space in bind pose.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoneOffsetMatrix(pub Matrix4);
+
+impl WorldMatrix {
+    /// The world transformation of a child node with the given local
+    /// transformation.
+    pub fn child(&self, local: &LocalMatrix) -> WorldMatrix {
+        WorldMatrix(mat4_mul(&self.0, &local.0))
+    }
+
+    /// The skinning matrix for a bone whose node has this world
+    /// transformation, mapping bind pose mesh space to posed space.
+    pub fn skinning(&self, offset: &BoneOffsetMatrix) -> Matrix4 {
+        mat4_mul(&self.0, &offset.0)
+    }
+}
+
+impl ::std::ops::Mul<LocalMatrix> for WorldMatrix {
+    type Output = WorldMatrix;
+
+    fn mul(self, local: LocalMatrix) -> WorldMatrix {
+        self.child(&local)
+    }
+}
+
 pub fn vec2(v: ffi::aiVector2D) -> Vector2 {
     [v.x, v.y]
 }
@@ -53,6 +89,198 @@ pub fn mat4(v: ffi::aiMatrix4x4) -> Matrix4 {
     ]
 }
 
+/// The identity matrix.
+pub const IDENTITY: Matrix4 = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// Matrix product `a * b`, i.e. `b` is applied first.
+pub fn mat4_mul(a: &Matrix4, b: &Matrix4) -> Matrix4 {
+    let mut ret = [[0.0; 4]; 4];
+    for r in 0..4 {
+        for c in 0..4 {
+            ret[r][c] = (0..4).map(|k| a[r][k] * b[k][c]).sum();
+        }
+    }
+    ret
+}
+
+/// Transforms a point (w = 1) by a matrix.
+pub fn transform_point(m: &Matrix4, p: Vector3) -> Vector3 {
+    let row = |r: &[f32; 4]| r[0] * p[0] + r[1] * p[1] + r[2] * p[2] + r[3];
+    [row(&m[0]), row(&m[1]), row(&m[2])]
+}
+
+/// Transforms a direction (w = 0) by a matrix.
+pub fn transform_vector(m: &Matrix4, v: Vector3) -> Vector3 {
+    let row = |r: &[f32; 4]| r[0] * v[0] + r[1] * v[1] + r[2] * v[2];
+    [row(&m[0]), row(&m[1]), row(&m[2])]
+}
+
+pub fn sub(a: Vector3, b: Vector3) -> Vector3 {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+pub fn dot(a: Vector3, b: Vector3) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+pub fn cross(a: Vector3, b: Vector3) -> Vector3 {
+    [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+pub fn length(v: Vector3) -> f32 {
+    dot(v, v).sqrt()
+}
+
+pub fn lerp(a: Vector3, b: Vector3, t: f32) -> Vector3 {
+    [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t, a[2] + (b[2] - a[2]) * t]
+}
+
+/// Spherical linear interpolation between two unit quaternions, along the
+/// shorter arc.
+pub fn quat_slerp(a: Quaternion, b: Quaternion, t: f32) -> Quaternion {
+    let mut cos = a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3];
+    let mut b = b;
+    if cos < 0.0 {
+        cos = -cos;
+        b = [-b[0], -b[1], -b[2], -b[3]];
+    }
+    let (wa, wb) = if cos > 0.9995 {
+        // nearly parallel, linear interpolation is exact enough
+        (1.0 - t, t)
+    } else {
+        let angle = cos.acos();
+        let sin = angle.sin();
+        (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+    };
+    let q = [
+        a[0] * wa + b[0] * wb,
+        a[1] * wa + b[1] * wb,
+        a[2] * wa + b[2] * wb,
+        a[3] * wa + b[3] * wb,
+    ];
+    let len = (q[0] * q[0] + q[1] * q[1] + q[2] * q[2] + q[3] * q[3]).sqrt();
+    [q[0] / len, q[1] / len, q[2] / len, q[3] / len]
+}
+
+/// The matrix applying scaling, rotation and translation, in this order.
+pub fn mat4_from_trs(translation: Vector3, rotation: Quaternion, scaling: Vector3) -> Matrix4 {
+    let [w, x, y, z] = rotation;
+    let r = [
+        [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - w * z), 2.0 * (x * z + w * y)],
+        [2.0 * (x * y + w * z), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - w * x)],
+        [2.0 * (x * z - w * y), 2.0 * (y * z + w * x), 1.0 - 2.0 * (x * x + y * y)],
+    ];
+    let mut ret = IDENTITY;
+    for row in 0..3 {
+        for col in 0..3 {
+            ret[row][col] = r[row][col] * scaling[col];
+        }
+        ret[row][3] = translation[row];
+    }
+    ret
+}
+
+/// The rotation part of a matrix without scaling or shearing as quaternion.
+pub fn quat_from_mat4(m: &Matrix4) -> Quaternion {
+    let trace = m[0][0] + m[1][1] + m[2][2];
+    if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        [0.25 * s, (m[2][1] - m[1][2]) / s, (m[0][2] - m[2][0]) / s, (m[1][0] - m[0][1]) / s]
+    } else if m[0][0] > m[1][1] && m[0][0] > m[2][2] {
+        let s = (1.0 + m[0][0] - m[1][1] - m[2][2]).sqrt() * 2.0;
+        [(m[2][1] - m[1][2]) / s, 0.25 * s, (m[0][1] + m[1][0]) / s, (m[0][2] + m[2][0]) / s]
+    } else if m[1][1] > m[2][2] {
+        let s = (1.0 + m[1][1] - m[0][0] - m[2][2]).sqrt() * 2.0;
+        [(m[0][2] - m[2][0]) / s, (m[0][1] + m[1][0]) / s, 0.25 * s, (m[1][2] + m[2][1]) / s]
+    } else {
+        let s = (1.0 + m[2][2] - m[0][0] - m[1][1]).sqrt() * 2.0;
+        [(m[1][0] - m[0][1]) / s, (m[0][2] + m[2][0]) / s, (m[1][2] + m[2][1]) / s, 0.25 * s]
+    }
+}
+
+/// An axis-aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Vector3,
+    pub max: Vector3,
+}
+
+impl Aabb {
+    /// The bounding box of a set of points, `None` if there are none.
+    pub fn from_points<I: IntoIterator<Item = Vector3>>(points: I) -> Option<Aabb> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut ret = Aabb { min: first, max: first };
+        for p in points {
+            ret.insert(p);
+        }
+        Some(ret)
+    }
+
+    /// Grows the box to contain `p`.
+    pub fn insert(&mut self, p: Vector3) {
+        for i in 0..3 {
+            self.min[i] = self.min[i].min(p[i]);
+            self.max[i] = self.max[i].max(p[i]);
+        }
+    }
+
+    /// The smallest box containing both boxes.
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        let mut ret = *self;
+        ret.insert(other.min);
+        ret.insert(other.max);
+        ret
+    }
+
+    pub fn size(&self) -> Vector3 {
+        sub(self.max, self.min)
+    }
+
+    pub fn center(&self) -> Vector3 {
+        [
+            (self.min[0] + self.max[0]) * 0.5,
+            (self.min[1] + self.max[1]) * 0.5,
+            (self.min[2] + self.max[2]) * 0.5,
+        ]
+    }
+
+    /// The box scaled uniformly about the origin.
+    pub fn scaled(&self, factor: f32) -> Aabb {
+        let s = |v: Vector3| [v[0] * factor, v[1] * factor, v[2] * factor];
+        Aabb::from_points(vec![s(self.min), s(self.max)]).unwrap()
+    }
+}
+
+/// Matches `text` against a glob pattern supporting `*` (any sequence,
+/// including an empty one) and `?` (any single character).
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // position of the last `*` in pattern and the text position it matched up to
+    let mut backtrack = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
 pub fn str<'a>(s: &'a ffi::aiString) -> Option<&'a str> {
     let len = s.length as usize;
     if len == 0 {
@@ -64,6 +292,47 @@ pub fn str<'a>(s: &'a ffi::aiString) -> Option<&'a str> {
     }
 }
 
+/// Converts `path` to the NUL-terminated string assimp expects.
+///
+/// Assimp's C API takes narrow strings, which it treats as UTF-8 on
+/// Windows, so non-Unicode paths can only be represented on Unix.
+pub fn path_to_cstring(path: &Path) -> Result<CString, String> {
+    #[cfg(unix)]
+    let bytes = {
+        use std::os::unix::ffi::OsStrExt;
+        path.as_os_str().as_bytes().to_vec()
+    };
+    #[cfg(not(unix))]
+    let bytes = match path.to_str() {
+        Some(path) => path.as_bytes().to_vec(),
+        None => return Err(format!("path is not valid unicode: {}", path.display())),
+    };
+    CString::new(bytes).map_err(|e| e.to_string())
+}
+
+/// Converts to an `aiString`, truncating to its capacity.
+pub fn to_ai_string(s: &str) -> ffi::aiString {
+    let mut ret = ffi::aiString::default();
+    let mut len = s.len().min(ret.data.len() - 1);
+    while !s.is_char_boundary(len) {
+        len -= 1;
+    }
+    for (dst, &src) in ret.data.iter_mut().zip(&s.as_bytes()[..len]) {
+        *dst = src as ::libc::c_char;
+    }
+    ret.length = len as _;
+    ret
+}
+
+pub fn to_ai_mat4(m: &Matrix4) -> ffi::aiMatrix4x4 {
+    ffi::aiMatrix4x4 {
+        a1: m[0][0], a2: m[0][1], a3: m[0][2], a4: m[0][3],
+        b1: m[1][0], b2: m[1][1], b3: m[1][2], b4: m[1][3],
+        c1: m[2][0], c2: m[2][1], c3: m[2][2], c4: m[2][3],
+        d1: m[3][0], d2: m[3][1], d3: m[3][2], d4: m[3][3],
+    }
+}
+
 pub unsafe fn slice<'a, T, U>(ptr: *const T, len: c_uint) -> &'a [U] {
     assert_eq!(mem::size_of::<T>(), mem::size_of::<U>());
 
@@ -73,3 +342,70 @@ pub unsafe fn slice<'a, T, U>(ptr: *const T, len: c_uint) -> &'a [U] {
     }
     slice::from_raw_parts(ptr as *const U, len)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PI_8: f32 = ::std::f32::consts::PI / 8.0;
+
+    fn assert_quat_eq(a: Quaternion, b: Quaternion) {
+        // q and -q are the same rotation
+        let d = a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3];
+        assert!((d.abs() - 1.0).abs() < 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn glob() {
+        assert!(glob_match("*", ""));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("mixamorig:*", "mixamorig:Hips"));
+        assert!(glob_match("*_L", "Arm_L"));
+        assert!(glob_match("a*b*c", "axxbyyc"));
+        assert!(glob_match("a*b", "abab"));
+        assert!(glob_match("Bone.00?", "Bone.001"));
+        assert!(glob_match("", ""));
+        assert!(!glob_match("", "a"));
+        assert!(!glob_match("Bone.00?", "Bone.00"));
+        assert!(!glob_match("*_L", "Arm_R"));
+        assert!(!glob_match("a*b", "abac"));
+    }
+
+    #[test]
+    fn slerp() {
+        let identity = [1.0, 0.0, 0.0, 0.0];
+        let half = 0.5f32.sqrt();
+        // 90 degrees about z
+        let z90 = [half, 0.0, 0.0, half];
+        assert_quat_eq(quat_slerp(identity, z90, 0.0), identity);
+        assert_quat_eq(quat_slerp(identity, z90, 1.0), z90);
+        let z45 = [PI_8.cos(), 0.0, 0.0, PI_8.sin()];
+        assert_quat_eq(quat_slerp(identity, z90, 0.5), z45);
+        // the shorter arc is taken for negated quaternions
+        let neg = [-z90[0], -z90[1], -z90[2], -z90[3]];
+        assert_quat_eq(quat_slerp(identity, neg, 0.5), z45);
+        // nearly parallel quaternions stay normalized
+        let q = quat_slerp(identity, [1.0, 1e-4, 0.0, 0.0], 0.5);
+        assert!((q.iter().map(|c| c * c).sum::<f32>() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn quat_round_trip() {
+        let half = 0.5f32.sqrt();
+        let quats = [
+            [1.0, 0.0, 0.0, 0.0],
+            [half, half, 0.0, 0.0],
+            [half, 0.0, half, 0.0],
+            [half, 0.0, 0.0, half],
+            // 180 degrees, trace -1, each branch of the decomposition
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+            [0.5, 0.5, -0.5, 0.5],
+        ];
+        for &q in &quats {
+            let m = mat4_from_trs([1.0, 2.0, 3.0], q, [1.0; 3]);
+            assert_quat_eq(quat_from_mat4(&m), q);
+        }
+    }
+}
@@ -1,20 +1,54 @@
-use anim::Animation;
+use anim::{Animation, NodeAnim};
 use camera::Camera;
-use light::Light;
-use material::Material;
+use config::{self, PropertyStore};
+use data::SceneData;
+use io::{self, FileSystem};
+use light::{Light, LightGrid};
+use limits::{ImportLimits, SizeLimitedFileSystem};
+use material::{Material, MaterialField, MaterialTable, TextureIdx, TextureRef, TextureType};
 use metadata::MetaData;
-use mesh::Mesh;
-use postprocess::PostProcessSteps;
+use mesh::{MaterialHandle, Mesh};
+use postprocess::{PostProcessSteps, REMOVE_COMPONENT};
 use texture::Texture;
-use prim::{self, Matrix4};
+use metadata::MetadataValue;
+use prim::{self, Aabb, LocalMatrix, Matrix4, Vector3, WorldMatrix};
 use ffi;
-use std::ffi::CStr;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::mem;
+use std::path::Path;
+use std::ptr;
 use libc::c_uint;
 
 // ++++++++++++++++++++ Node ++++++++++++++++++++
 
+/// Index into `Scene::meshes()`.
+#[deprecated(note = "use `MeshHandle`")]
 pub type MeshIdx = c_uint;
 
+/// Refers to a mesh of a scene, see `Scene::mesh()`.
+///
+/// The wrapped value is the index into `Scene::meshes()`. `Node::meshes()`
+/// reinterprets assimp's index array as handles, hence the layout.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MeshHandle(pub u32);
+
+impl MeshHandle {
+    /// The index into `Scene::meshes()`.
+    pub fn idx(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Refers to a node of a scene, see `Scene::node()`.
+///
+/// Assimp doesn't number nodes, so the wrapped value is the position of
+/// the node in a depth-first, pre-order walk of the hierarchy (the root
+/// node is `NodeHandle(0)`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeHandle(pub u32);
+
 ai_ptr_type!{
     /// A node in the imported hierarchy.
     ///
@@ -25,6 +59,12 @@ ai_ptr_type!{
     type Node: ffi::aiNode;
 }
 
+impl<'a> Clone for Node<'a> {
+    fn clone(&self) -> Self {
+        unsafe { Node::from_ptr(self.as_ptr()) }
+    }
+}
+
 impl<'a> Node<'a> {
     /// The name of the node.
     ///
@@ -56,6 +96,34 @@ impl<'a> Node<'a> {
         prim::mat4(self.raw().mTransformation)
     }
 
+    /// The transformation relative to the scene root, i.e. the product of
+    /// the transformations of this node and all of its ancestors.
+    pub fn global_transform(&self) -> Matrix4 {
+        let mut ret = self.transform();
+        let mut node = self.parent();
+        while let Some(parent) = node {
+            ret = prim::mat4_mul(&parent.transform(), &ret);
+            node = parent.parent();
+        }
+        ret
+    }
+
+    /// `transform()`, tagged with its coordinate space.
+    pub fn local_transform(&self) -> LocalMatrix {
+        LocalMatrix(self.transform())
+    }
+
+    /// `global_transform()`, tagged with its coordinate space.
+    pub fn world_transform(&self) -> WorldMatrix {
+        WorldMatrix(self.global_transform())
+    }
+
+    /// This node or the first descendant (in depth-first order) with the
+    /// given name.
+    pub fn find(&self, name: &str) -> Option<Node<'a>> {
+        self.descendants().find(|node| node.name() == Some(name))
+    }
+
     /// Parent node. NULL if this node is the root node.
     pub fn parent(&self) -> Option<Self> {
         if self.raw().mParent.is_null() {
@@ -69,10 +137,23 @@ impl<'a> Node<'a> {
         unsafe { Self::slice(self.raw().mChildren, self.raw().mNumChildren) }
     }
 
+    /// The child nodes of this node, borrowing the scene instead of
+    /// `self`, so they can outlive it (e.g. on a traversal stack).
+    pub fn children_iter(&self) -> impl Iterator<Item = Node<'a>> + 'a {
+        let children: &'a [Node<'a>] = unsafe { Node::slice(self.raw().mChildren, self.raw().mNumChildren) };
+        children.iter().cloned()
+    }
+
+    /// This node and all of its descendants, in depth-first pre-order
+    /// (the order `NodeHandle` numbers them in).
+    pub fn descendants(&self) -> Descendants<'a> {
+        Descendants { stack: vec![self.clone()] }
+    }
+
     /// The meshes of this node.
     ///
-    /// Each entry is an index into the  mesh list of the #aiScene.
-    pub fn meshes(&self) -> &[MeshIdx] {
+    /// Each entry refers to a mesh of the scene, see `Scene::mesh()`.
+    pub fn meshes(&self) -> &[MeshHandle] {
         unsafe { prim::slice(self.raw().mMeshes, self.raw().mNumMeshes) }
     }
 
@@ -89,6 +170,23 @@ impl<'a> Node<'a> {
     }
 }
 
+/// Iterator over a node and its descendants, see `Node::descendants()`.
+pub struct Descendants<'a> {
+    stack: Vec<Node<'a>>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = Node<'a>;
+
+    fn next(&mut self) -> Option<Node<'a>> {
+        let node = self.stack.pop()?;
+        let first = self.stack.len();
+        self.stack.extend(node.children_iter());
+        self.stack[first..].reverse();
+        Some(node)
+    }
+}
+
 // ++++++++++++++++++++ Scene ++++++++++++++++++++
 
 bitflags!{
@@ -139,20 +237,73 @@ bitflags!{
 }
 ai_impl_enum!(SceneFlags, c_uint);
 
+/// What an imported scene holds, see `Scene::content()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SceneContent {
+    /// A complete scene with a node hierarchy.
+    Complete,
+    /// An incomplete scene holding only materials, e.g. an imported
+    /// material library.
+    Materials,
+    /// An incomplete scene holding animations but no meshes, e.g. an
+    /// animation-only or skeleton file.
+    Animations,
+    /// Any other incomplete scene.
+    Partial,
+}
+
 /// The root structure of the imported data.
 ///
 /// Everything that was imported from the given file can be accessed from here.
 /// Objects of this class are generally maintained and owned by Assimp, not
 /// by the caller. You shouldn't want to instance it, nor should you ever try to
 /// delete a given scene on your own.
+///
+/// A `Scene` is `Send` and `Sync`, so it can e.g. be imported on a worker
+/// thread and handed to the main thread. The wrappers borrowed from it
+/// are neither:
+///
+/// ```compile_fail,E0277
+/// fn assert_send<T: Send>() {}
+/// assert_send::<assimp_import::scene::Node>();
+/// ```
+///
+/// ```compile_fail,E0277
+/// fn assert_send<T: Send>() {}
+/// assert_send::<assimp_import::mesh::Mesh>();
+/// ```
 pub struct Scene {
     raw: &'static ffi::aiScene,
+    /// Whether the scene was created by `duplicate()` rather than an
+    /// import, which determines how it has to be released.
+    copied: bool,
+}
+
+// The aiScene is exclusively owned by this object and assimp keeps no
+// thread-local or otherwise thread-bound state for it, so it can be
+// released on any thread. Everything taking `&self` only reads from it
+// (assimp's material getters, aiCopyScene and aiExportScene don't modify
+// the scene either), so sharing it is fine, too. The borrowed wrappers
+// (`Node`, `Mesh`, ...) stay !Send and !Sync; to use the data on several
+// threads at once, share the `Scene` itself or convert it with
+// `to_owned_data()`.
+unsafe impl Send for Scene {}
+unsafe impl Sync for Scene {}
+
+#[allow(dead_code)]
+fn _assert_send_sync() {
+    fn f<T: Send + Sync>() {}
+    f::<Scene>();
 }
 
 impl Drop for Scene {
     fn drop(&mut self) {
         unsafe {
-            ffi::aiReleaseImport(self.raw as *const _);
+            if self.copied {
+                ffi::aiFreeScene(self.raw as *const _);
+            } else {
+                ffi::aiReleaseImport(self.raw as *const _);
+            }
         }
     }
 }
@@ -160,7 +311,52 @@ impl Drop for Scene {
 impl Scene {
     pub unsafe fn from_ptr(ptr: *const ffi::aiScene) -> Self {
         assert!(!ptr.is_null());
-        Scene { raw: &*ptr }
+        Scene { raw: &*ptr, copied: false }
+    }
+
+    /// Creates a deep copy of this scene, e.g. to keep a pristine copy
+    /// around while running destructive post-processing on the other.
+    ///
+    /// The copy is detached from the importer, so it can't be
+    /// post-processed with `apply_postprocess()` itself.
+    pub fn duplicate(&self) -> Scene {
+        let mut ptr = ptr::null_mut();
+        unsafe {
+            ffi::aiCopyScene(self.raw, &mut ptr);
+            assert!(!ptr.is_null());
+            Scene { raw: &*ptr, copied: true }
+        }
+    }
+
+    /// The underlying `aiScene`, as an escape hatch for data the safe API
+    /// doesn't cover yet.
+    ///
+    /// The scene is owned by assimp and released when this object is
+    /// dropped, so neither the struct nor anything reachable from its
+    /// pointers may outlive the borrow. All of it must be treated as
+    /// read-only; in particular, don't swap out arrays or pointers, as
+    /// assimp frees them on release.
+    pub fn raw(&self) -> &ffi::aiScene {
+        self.raw
+    }
+
+    /// Refuses to import if the major version of the linked assimp differs
+    /// from the one the pregenerated bindings were written for, rather
+    /// than reading garbage. Bindings generated from the installed headers
+    /// aren't tied to a version, so nothing is checked for them.
+    fn check_version() -> Result<(), String> {
+        let expected = match ffi::BINDINGS_VERSION_MAJOR {
+            Some(expected) => expected,
+            None => return Ok(()),
+        };
+        let (major, minor, rev) = ::version();
+        if major != expected {
+            return Err(format!(
+                "linked assimp {}.{}.{} is incompatible with the bindings for assimp {}.x",
+                major, minor, rev, expected
+            ));
+        }
+        Ok(())
     }
 
     fn get_error_string() -> String {
@@ -169,18 +365,19 @@ impl Scene {
         }
     }
 
+    /// Imports the file at `path` and applies the post-processing `flags`.
+    ///
     /// TODO
     ///
     /// * return error (with log)
     /// * also: warnings?
-    /// * doc
-    /// * aiPropertyStore?
     #[allow(non_snake_case)]
-    pub fn from_file(path: &str, flags: PostProcessSteps) -> Result<Scene, String> {
-        let pFile = path.as_ptr() as *const _;
+    pub fn from_path<P: AsRef<Path>>(path: P, flags: PostProcessSteps) -> Result<Scene, String> {
+        let path = prim::path_to_cstring(path.as_ref())?;
         let pFlags = flags.bits() as c_uint;
+        Self::check_version()?;
         unsafe {
-            let ptr = ffi::aiImportFile(pFile, pFlags);
+            let ptr = ffi::aiImportFile(path.as_ptr(), pFlags);
             if ptr.is_null() {
                 return Err(Self::get_error_string())
             }
@@ -188,12 +385,17 @@ impl Scene {
         }
     }
 
+    /// Same as `from_path()`.
+    #[deprecated(note = "use `Scene::from_path()`, which also accepts non-UTF-8 paths")]
+    pub fn from_file(path: &str, flags: PostProcessSteps) -> Result<Scene, String> {
+        Self::from_path(path, flags)
+    }
+
     /// TODO return error (with log)
     ///
     /// * return error (with log)
     /// * also: warnings?
     /// * doc
-    /// * aiPropertyStore?
     #[allow(non_snake_case)]
     pub fn from_bytes(bytes: &[u8], hint: &str, flags: PostProcessSteps) -> Result<Scene, String> {
         let pBuffer = bytes.as_ptr() as *const _;
@@ -201,6 +403,7 @@ impl Scene {
         let pFlags = flags.bits() as c_uint;
         let hint = format!("{}\0", hint);
         let pHint = hint.as_ptr() as *const _;
+        Self::check_version()?;
         unsafe {
             let ptr = ffi::aiImportFileFromMemory(pBuffer, pLength, pFlags, pHint);
             if ptr.is_null() {
@@ -210,6 +413,180 @@ impl Scene {
         }
     }
 
+    /// Summarizes the contents of a file with a minimal import, e.g. for
+    /// asset browsers.
+    ///
+    /// The file is still parsed completely. The only post-processing step
+    /// is RemoveComponent, which drops all vertex attributes besides
+    /// positions and the bone weights right after parsing, and validation
+    /// is skipped. This saves the memory and time of everything else
+    /// `from_path()` would do with the data, the counts in the summary
+    /// are unaffected.
+    pub fn peek<P: AsRef<Path>>(path: P) -> Result<SceneSummary, String> {
+        let mut props = PropertyStore::new();
+        props.set_int(
+            config::PP_RVC_FLAGS,
+            config::COMPONENT_NORMALS
+                | config::COMPONENT_TANGENTS_AND_BITANGENTS
+                | config::COMPONENT_COLORS
+                | config::COMPONENT_TEXCOORDS
+                | config::COMPONENT_BONEWEIGHTS,
+        );
+        let scene = Self::from_file_with_properties(path, REMOVE_COMPONENT, &props)?;
+        Ok(scene.summary())
+    }
+
+    /// The memory used by this scene in bytes, as reported by assimp.
+    pub fn memory_info(&self) -> MemoryInfo {
+        let mut info = ffi::aiMemoryInfo::default();
+        unsafe { ffi::aiGetMemoryRequirements(self.raw, &mut info) };
+        MemoryInfo {
+            textures: info.textures as usize,
+            materials: info.materials as usize,
+            meshes: info.meshes as usize,
+            nodes: info.nodes as usize,
+            animations: info.animations as usize,
+            cameras: info.cameras as usize,
+            lights: info.lights as usize,
+            total: info.total as usize,
+        }
+    }
+
+    /// Deep copies this scene into owned data, which can outlive it.
+    pub fn to_owned_data(&self) -> SceneData {
+        SceneData::from_scene(self)
+    }
+
+    /// Counts and names of the contents of this scene.
+    pub fn summary(&self) -> SceneSummary {
+        let mut node_count = 0;
+        if let Some(root) = self.root_node() {
+            self.visit_nodes(&root, &prim::IDENTITY, &mut |_, _| node_count += 1);
+        }
+        let meshes = self.meshes();
+        SceneSummary {
+            flags: self.flags(),
+            node_count,
+            vertex_count: meshes.iter().map(|m| m.vertices().len()).sum(),
+            face_count: meshes.iter().map(|m| m.faces().len()).sum(),
+            mesh_names: meshes.iter().map(|m| m.name().unwrap_or("").to_owned()).collect(),
+            material_names: self.materials().iter().map(|m| m.material_properties().name).collect(),
+            animations: self
+                .animations()
+                .iter()
+                .map(|a| (a.name().unwrap_or("").to_owned(), a.duration_seconds()))
+                .collect(),
+            texture_count: self.textures().len(),
+            light_count: self.lights().len(),
+            camera_count: self.cameras().len(),
+        }
+    }
+
+    /// Like `from_path()`, but configures the importers and post-processing
+    /// steps with `props`.
+    #[allow(non_snake_case)]
+    pub fn from_file_with_properties<P: AsRef<Path>>(path: P, flags: PostProcessSteps, props: &PropertyStore) -> Result<Scene, String> {
+        Self::from_file_ex(path, flags, None, Some(props))
+    }
+
+    /// Like `from_path()`, but reads the file and all files it references
+    /// through `fs` instead of from disk.
+    pub fn from_file_with_io<P: AsRef<Path>>(path: P, flags: PostProcessSteps, fs: &mut dyn FileSystem) -> Result<Scene, String> {
+        Self::from_file_ex(path, flags, Some(fs), None)
+    }
+
+    /// Imports through a custom file system and/or with a property store,
+    /// see `from_file_with_io()` and `from_file_with_properties()`.
+    #[allow(non_snake_case)]
+    pub fn from_file_ex<P: AsRef<Path>>(
+        path: P,
+        flags: PostProcessSteps,
+        mut fs: Option<&mut dyn FileSystem>,
+        props: Option<&PropertyStore>,
+    ) -> Result<Scene, String> {
+        let path = prim::path_to_cstring(path.as_ref())?;
+        let pFlags = flags.bits() as c_uint;
+        Self::check_version()?;
+        let mut file_io = fs.as_mut().map(io::file_io);
+        let pFS = file_io.as_mut().map_or(ptr::null_mut(), |io| io as *mut _);
+        let pProps = props.map_or(ptr::null(), |props| props.as_ptr());
+        unsafe {
+            let ptr = ffi::aiImportFileExWithProperties(path.as_ptr(), pFlags, pFS, pProps);
+            if ptr.is_null() {
+                return Err(Self::get_error_string())
+            }
+            Ok(Self::from_ptr(ptr))
+        }
+    }
+
+    /// Like `from_bytes()`, but configures the importers and
+    /// post-processing steps with `props`.
+    #[allow(non_snake_case)]
+    pub fn from_bytes_with_properties(bytes: &[u8], hint: &str, flags: PostProcessSteps, props: &PropertyStore) -> Result<Scene, String> {
+        let pBuffer = bytes.as_ptr() as *const _;
+        let pLength = bytes.len() as c_uint;
+        let pFlags = flags.bits() as c_uint;
+        let hint = CString::new(hint).map_err(|e| e.to_string())?;
+        Self::check_version()?;
+        unsafe {
+            let ptr = ffi::aiImportFileFromMemoryWithProperties(pBuffer, pLength, pFlags, hint.as_ptr(), props.as_ptr());
+            if ptr.is_null() {
+                return Err(Self::get_error_string())
+            }
+            Ok(Self::from_ptr(ptr))
+        }
+    }
+
+    /// Like `from_path()`, but enforces `limits`.
+    ///
+    /// The size of the file and of the files it references is checked
+    /// before assimp reads them. All other limits are only checked after
+    /// the import, releasing the scene again if it exceeds any of them;
+    /// see `ImportLimits` for what that does and doesn't protect against.
+    pub fn from_file_with_limits<P: AsRef<Path>>(path: P, flags: PostProcessSteps, limits: &ImportLimits) -> Result<Scene, String> {
+        let mut fs = SizeLimitedFileSystem { limits, exceeded: None };
+        let scene = match Self::from_file_with_io(path, flags, &mut fs) {
+            Ok(scene) => scene,
+            Err(e) => return Err(fs.exceeded.unwrap_or(e)),
+        };
+        limits.check_scene(&scene)?;
+        Ok(scene)
+    }
+
+    /// Like `from_bytes()`, but enforces `limits`.
+    ///
+    /// The buffer size is checked before importing, all other limits only
+    /// after the import (see `from_file_with_limits()`). The scene is
+    /// released again if it exceeds any of them.
+    pub fn from_bytes_with_limits(bytes: &[u8], hint: &str, flags: PostProcessSteps, limits: &ImportLimits) -> Result<Scene, String> {
+        limits.check_file_size(bytes.len() as u64)?;
+        let scene = Self::from_bytes(bytes, hint, flags)?;
+        limits.check_scene(&scene)?;
+        Ok(scene)
+    }
+
+    /// Applies post-processing `steps` to this already imported scene,
+    /// e.g. to triangulate only after inspecting it.
+    ///
+    /// Post-processing happens in place. It takes the scene by value
+    /// because assimp releases the scene if a step fails (currently only
+    /// `VALIDATE_DATA_STRUCTURE` can), leaving nothing to return.
+    pub fn apply_postprocess(self, steps: PostProcessSteps) -> Result<Scene, String> {
+        if self.copied {
+            return Err("can't post-process a duplicated scene, only imported ones".to_owned());
+        }
+        let ptr = self.raw as *const ffi::aiScene;
+        // re-wrapped below on success, released by assimp on failure
+        mem::forget(self);
+        unsafe {
+            let ptr = ffi::aiApplyPostProcessing(ptr, steps.bits() as c_uint);
+            if ptr.is_null() {
+                return Err(Self::get_error_string())
+            }
+            Ok(Self::from_ptr(ptr))
+        }
+    }
+
     /// Any combination of the AI_SCENE_FLAGS_XXX flags.
     ///
     /// By default
@@ -220,14 +597,71 @@ impl Scene {
         unsafe { SceneFlags::from_ffi(self.raw.mFlags) }
     }
 
+    /// Whether the `INCOMPLETE` flag is set.
+    ///
+    /// Incomplete scenes are the result of importing e.g. material
+    /// libraries or animation-only files. They may lack a root node
+    /// and meshes, so only the arrays actually present should be used.
+    pub fn is_incomplete(&self) -> bool {
+        self.flags().contains(INCOMPLETE)
+    }
+
+    /// Classifies the scene by the data it holds, so materials-only and
+    /// animations-only imports can be handled without probing each array.
+    pub fn content(&self) -> SceneContent {
+        if !self.is_incomplete() && self.root_node().is_some() {
+            SceneContent::Complete
+        } else if !self.meshes().is_empty() {
+            SceneContent::Partial
+        } else if !self.animations().is_empty() {
+            SceneContent::Animations
+        } else if !self.materials().is_empty() && self.cameras().is_empty() && self.lights().is_empty() {
+            SceneContent::Materials
+        } else {
+            SceneContent::Partial
+        }
+    }
+
+    /// The materials of a material library import, `None` if the scene
+    /// holds anything else. See `content()`.
+    pub fn material_library(&self) -> Option<&[Material<'_>]> {
+        if self.content() == SceneContent::Materials { Some(self.materials()) } else { None }
+    }
+
+    /// The animations of an animation-only import, `None` if the scene
+    /// holds anything else. See `content()`.
+    pub fn animation_library(&self) -> Option<&[Animation<'_>]> {
+        if self.content() == SceneContent::Animations { Some(self.animations()) } else { None }
+    }
+
+    /// The scene if it is complete, an error naming what is missing
+    /// otherwise. Use this to reject scenes that only load partially.
+    pub fn require_complete(&self) -> Result<&Scene, String> {
+        match self.content() {
+            SceneContent::Complete => Ok(self),
+            SceneContent::Materials => Err("scene is incomplete: only materials were imported".to_owned()),
+            SceneContent::Animations => Err("scene is incomplete: only animations were imported".to_owned()),
+            SceneContent::Partial => Err("scene is incomplete".to_owned()),
+        }
+    }
+
     /// The root node of the hierarchy.
     ///
     /// There will always be at least the root node if the import
     /// was successful (and no special flags have been set).
     /// Presence of further nodes depends on the format and content
-    /// of the imported file.
-    pub fn root_node(&self) -> Node {
-        unsafe { Node::from_ptr(self.raw.mRootNode) }
+    /// of the imported file. `None` if the scene is incomplete and
+    /// has no hierarchy.
+    pub fn root_node(&self) -> Option<Node<'_>> {
+        if self.raw.mRootNode.is_null() {
+            return None;
+        }
+        unsafe { Some(Node::from_ptr(self.raw.mRootNode)) }
+    }
+
+    /// The first node with the given name, see `Node::find()`.
+    pub fn find_node(&self, name: &str) -> Option<Node<'_>> {
+        self.root_node()?.find(name)
     }
 
     /// The array of meshes.
@@ -236,7 +670,7 @@ impl Scene {
     /// this array. The array is mNumMeshes in size. If the
     /// AI_SCENE_FLAGS_INCOMPLETE flag is not set there will always
     /// be at least ONE material.
-    pub fn meshes(&self) -> &[Mesh] {
+    pub fn meshes(&self) -> &[Mesh<'_>] {
         unsafe { Mesh::slice(self.raw.mMeshes, self.raw.mNumMeshes) }
     }
 
@@ -246,7 +680,7 @@ impl Scene {
     /// array. The array is mNumMaterials in size. If the
     /// AI_SCENE_FLAGS_INCOMPLETE flag is not set there will always
     /// be at least ONE material.
-    pub fn materials(&self) -> &[Material] {
+    pub fn materials(&self) -> &[Material<'_>] {
         unsafe { Material::slice(self.raw.mMaterials, self.raw.mNumMaterials) }
     }
 
@@ -254,7 +688,7 @@ impl Scene {
     ///
     /// All animations imported from the given file are listed here.
     /// The array is mNumAnimations in size.
-    pub fn animations(&self) -> &[Animation] {
+    pub fn animations(&self) -> &[Animation<'_>] {
         unsafe { Animation::slice(self.raw.mAnimations, self.raw.mNumAnimations) }
     }
 
@@ -263,7 +697,7 @@ impl Scene {
     /// Not many file formats embed their textures into the file.
     /// An example is Quake's MDL format (which is also used by
     /// some GameStudio versions)
-    pub fn textures(&self) -> &[Texture] {
+    pub fn textures(&self) -> &[Texture<'_>] {
         unsafe { Texture::slice(self.raw.mTextures, self.raw.mNumTextures) }
     }
 
@@ -271,7 +705,7 @@ impl Scene {
     ///
     /// All light sources imported from the given file are
     /// listed here. The array is mNumLights in size.
-    pub fn lights(&self) -> &[Light] {
+    pub fn lights(&self) -> &[Light<'_>] {
         unsafe { Light::slice(self.raw.mLights, self.raw.mNumLights) }
     }
 
@@ -281,7 +715,437 @@ impl Scene {
     /// The array is mNumCameras in size. The first camera in the
     /// array (if existing) is the default camera view into
     /// the scene.
-    pub fn cameras(&self) -> &[Camera] {
+    pub fn cameras(&self) -> &[Camera<'_>] {
         unsafe { Camera::slice(self.raw.mCameras, self.raw.mNumCameras) }
     }
+
+    /// The mesh referred to by `handle`, `None` if it's out of range.
+    pub fn mesh(&self, handle: MeshHandle) -> Option<&Mesh<'_>> {
+        self.meshes().get(handle.idx())
+    }
+
+    /// The material referred to by `handle`, `None` if it's out of range.
+    pub fn material(&self, handle: MaterialHandle) -> Option<&Material<'_>> {
+        self.materials().get(handle.idx())
+    }
+
+    /// The node referred to by `handle`, `None` if it's out of range.
+    ///
+    /// This walks the hierarchy up to the node, so it takes O(n) per call.
+    /// To visit many nodes, iterate `root_node()?.descendants()` instead,
+    /// which yields them in handle order.
+    pub fn node(&self, handle: NodeHandle) -> Option<Node<'_>> {
+        self.root_node()?.descendants().nth(handle.0 as usize)
+    }
+
+    /// The handle of `node`, `None` if it belongs to another scene.
+    ///
+    /// Like `node()`, this walks the hierarchy and takes O(n) per call.
+    pub fn node_handle(&self, node: &Node) -> Option<NodeHandle> {
+        self.root_node()?
+            .descendants()
+            .position(|current| current.as_ptr() == node.as_ptr())
+            .map(|idx| NodeHandle(idx as u32))
+    }
+
+    /// Groups meshes which most likely originate from the same mesh in the
+    /// source file.
+    ///
+    /// Importers split meshes with several materials into one mesh per
+    /// material and give all parts the same name (see `Mesh::name()`).
+    /// Meshes sharing a name are therefore grouped together, in order of
+    /// first appearance. Unnamed meshes each form a group of their own.
+    pub fn original_mesh_groups(&self) -> Vec<MeshGroup<'_>> {
+        let mut groups: Vec<MeshGroup> = Vec::new();
+        let mut by_name: HashMap<&str, usize> = HashMap::new();
+        for (idx, mesh) in self.meshes().iter().enumerate() {
+            let idx = MeshHandle(idx as u32);
+            if let Some(name) = mesh.name() {
+                if let Some(&group) = by_name.get(name) {
+                    groups[group].meshes.push(idx);
+                    continue;
+                }
+                by_name.insert(name, groups.len());
+            }
+            groups.push(MeshGroup { name: mesh.name(), meshes: vec![idx] });
+        }
+        groups
+    }
+
+    /// Gathers world space triangles suitable as navmesh input.
+    ///
+    /// Triangles are kept if the angle between their normal (by
+    /// counter-clockwise winding) and the +Y axis is at most
+    /// `max_slope_deg` degrees. `filter` selects which mesh instances to
+    /// consider, e.g. by node name or material. One surface is returned
+    /// per mesh instance with walkable triangles, in depth-first order.
+    pub fn walkable_triangles<F>(&self, max_slope_deg: f32, mut filter: F) -> Vec<WalkableSurface>
+    where
+        F: FnMut(&Node, &Mesh) -> bool,
+    {
+        let mut ret = Vec::new();
+        let root = match self.root_node() {
+            Some(root) => root,
+            None => return ret,
+        };
+        let min_cos = max_slope_deg.to_radians().cos();
+        self.visit_nodes(&root, &prim::IDENTITY, &mut |node, transform| {
+            for &handle in node.meshes() {
+                let mesh = match self.mesh(handle) {
+                    Some(mesh) => mesh,
+                    None => continue,
+                };
+                if !filter(node, mesh) {
+                    continue;
+                }
+                let triangles: Vec<[Vector3; 3]> = mesh
+                    .triangles()
+                    .map(|tri| {
+                        [
+                            prim::transform_point(transform, tri[0]),
+                            prim::transform_point(transform, tri[1]),
+                            prim::transform_point(transform, tri[2]),
+                        ]
+                    })
+                    .filter(|&[a, b, c]| {
+                        let n = prim::cross(prim::sub(b, a), prim::sub(c, a));
+                        let len = prim::length(n);
+                        len > 0.0 && n[1] / len >= min_cos
+                    })
+                    .collect();
+                if !triangles.is_empty() {
+                    ret.push(WalkableSurface { node: node.name().unwrap_or("").to_owned(), mesh: handle, triangles });
+                }
+            }
+        });
+        ret
+    }
+
+    /// Assigns the lights of the scene to the cells of a uniform grid over
+    /// `bounds`, for clustered or tiled shading of static scenes. See
+    /// `LightGrid`. `None` if `cell_size` isn't a positive, finite number or
+    /// the grid would exceed `MAX_LIGHT_GRID_CELLS`.
+    pub fn light_grid(&self, bounds: Aabb, cell_size: f32) -> Option<LightGrid> {
+        LightGrid::build(self, bounds, cell_size)
+    }
+
+    /// Packs the constants of all materials into a GPU-ready buffer, with
+    /// the given fields in each record. See `MaterialTable`.
+    pub fn material_table(&self, fields: &[MaterialField]) -> MaterialTable {
+        MaterialTable::build(self, fields)
+    }
+
+    /// Cross-references every texture with the material slots using it.
+    ///
+    /// Embedded textures come first, in the order of `textures()` and
+    /// including those no material refers to (with empty `uses`), followed
+    /// by external textures in order of first use. External paths are
+    /// compared verbatim.
+    pub fn texture_usage(&self) -> Vec<TextureUsage> {
+        let mut ret: Vec<TextureUsage> = (0..self.textures().len())
+            .map(|idx| TextureUsage { texture: TextureRef::Embedded(idx as TextureIdx), uses: Vec::new() })
+            .collect();
+        let mut external: HashMap<TextureRef, usize> = HashMap::new();
+
+        for (material_idx, material) in self.materials().iter().enumerate() {
+            for tex_ty in (0x1..0xD).map(|ty| unsafe { TextureType::from_ffi(ty) }) {
+                for idx in 0..material.count_texture_properties(tex_ty) {
+                    let props = match material.texture_properties(tex_ty, idx) {
+                        Some(props) => props,
+                        None => continue,
+                    };
+                    let texture = TextureRef::parse(&props.texture_ref);
+                    let usage_idx = match texture {
+                        TextureRef::Embedded(embedded) if (embedded as usize) < ret.len() => embedded as usize,
+                        _ => *external.entry(texture.clone()).or_insert_with(|| {
+                            ret.push(TextureUsage { texture, uses: Vec::new() });
+                            ret.len() - 1
+                        }),
+                    };
+                    ret[usage_idx].uses.push(TextureUse { material: MaterialHandle(material_idx as u32), tex_ty, idx });
+                }
+            }
+        }
+        ret
+    }
+
+    /// Node names shared by several nodes.
+    ///
+    /// Bones and animation channels refer to nodes by name, so they can only
+    /// address one of the nodes of a collision; collisions with
+    /// `bones`/`channels` set therefore break skinning or animation.
+    /// Sorted by name.
+    pub fn name_collisions(&self) -> Vec<NameCollision> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        if let Some(root) = self.root_node() {
+            self.visit_nodes(&root, &prim::IDENTITY, &mut |node, _| {
+                *counts.entry(node.name().unwrap_or("").to_owned()).or_insert(0) += 1;
+            });
+        }
+
+        let mut ret: Vec<NameCollision> = counts
+            .into_iter()
+            .filter(|&(_, count)| count > 1)
+            .map(|(name, count)| {
+                let bones = self.meshes().iter().any(|m| m.bones().iter().any(|b| b.name() == name));
+                let channels = self
+                    .animations()
+                    .iter()
+                    .any(|a| a.channels().iter().any(|c| c.node_name() == name));
+                NameCollision { name, count, bones, channels }
+            })
+            .collect();
+        ret.sort_by(|a, b| a.name.cmp(&b.name));
+        ret
+    }
+
+    /// Searches the metadata of all nodes for keys matching `pattern`.
+    ///
+    /// The pattern is matched against the whole key and may contain the
+    /// wildcards `*` (any sequence) and `?` (any single character), e.g.
+    /// `"UserProperties*"`. Matches are returned in depth-first order.
+    pub fn query_metadata(&self, pattern: &str) -> Vec<MetadataMatch<'_>> {
+        let mut ret = Vec::new();
+        for node in self.root_node().iter().flat_map(|root| root.descendants()) {
+            let meta = match node.meta_data() {
+                Some(meta) => meta,
+                None => continue,
+            };
+            for (key, value) in meta.iter() {
+                if prim::glob_match(pattern, key) {
+                    ret.push(MetadataMatch { node: node.clone(), key, value });
+                }
+            }
+        }
+        ret
+    }
+
+    /// Resolves the node animation channels of all animations to the nodes
+    /// they animate.
+    ///
+    /// Channels are keyed by the path of their node, i.e. the names from
+    /// the root node down to the node joined with `/`. If several nodes
+    /// share the channel's name, the first one in depth-first order is
+    /// used. Channels whose node doesn't exist end up in `missing`.
+    pub fn channels_by_node(&self) -> NodeChannels<'_> {
+        let mut paths = HashMap::new();
+        if let Some(root) = self.root_node() {
+            Self::collect_paths(&root, &mut paths);
+        }
+
+        let mut ret = NodeChannels { by_path: HashMap::new(), missing: Vec::new() };
+        for animation in self.animations() {
+            for channel in animation.channels() {
+                let entry = NodeChannel { animation, channel };
+                match paths.get(channel.node_name()) {
+                    Some(path) => ret.by_path.entry(path.clone()).or_insert_with(Vec::new).push(entry),
+                    None => ret.missing.push(entry),
+                }
+            }
+        }
+        ret
+    }
+
+    /// Maps the names of `node` and its descendants to their paths.
+    fn collect_paths(root: &Node, paths: &mut HashMap<String, String>) {
+        // iterative, hostile files may nest deep enough to overflow the stack
+        let mut stack = vec![(root.clone(), String::new())];
+        while let Some((node, parent)) = stack.pop() {
+            let name = node.name().unwrap_or("");
+            let path = if parent.is_empty() { name.to_owned() } else { format!("{}/{}", parent, name) };
+            paths.entry(name.to_owned()).or_insert_with(|| path.clone());
+            let first = stack.len();
+            stack.extend(node.children_iter().map(|child| (child, path.clone())));
+            stack[first..].reverse();
+        }
+    }
+
+    /// The bounding box of all mesh instances in world space.
+    ///
+    /// `None` if the scene has no hierarchy or no geometry.
+    pub fn dimensions(&self) -> Option<Dimensions> {
+        let root = self.root_node()?;
+        let mut aabb: Option<Aabb> = None;
+        self.visit_nodes(&root, &prim::IDENTITY, &mut |node, transform| {
+            for &handle in node.meshes() {
+                let mesh = match self.mesh(handle) {
+                    Some(mesh) => mesh,
+                    None => continue,
+                };
+                let points = mesh.vertices().iter().map(|&v| prim::transform_point(transform, v));
+                if let Some(bounds) = Aabb::from_points(points) {
+                    aabb = Some(aabb.map_or(bounds, |a| a.union(&bounds)));
+                }
+            }
+        });
+
+        let meters_per_unit = self.meters_per_unit();
+        aabb.map(|aabb| Dimensions { aabb, aabb_meters: aabb.scaled(meters_per_unit), meters_per_unit })
+    }
+
+    /// The length of one scene unit in meters.
+    ///
+    /// Taken from the `UnitScaleFactor` metadata of the root node (given in
+    /// centimeters per unit, as written by the FBX importer). Scenes without
+    /// this information are assumed to be in meters.
+    pub fn meters_per_unit(&self) -> f32 {
+        let meta = self.root_node().and_then(|root| root.meta_data());
+        let factor = meta.and_then(|meta| match meta.get("UnitScaleFactor") {
+            Some(MetadataValue::F32(f)) => Some(f),
+            Some(MetadataValue::I32(i)) => Some(i as f32),
+            Some(MetadataValue::U64(u)) => Some(u as f32),
+            _ => None,
+        });
+        match factor {
+            Some(f) if f > 0.0 => f / 100.0,
+            _ => 1.0,
+        }
+    }
+
+    /// Calls `f` for `node` and all of its descendants in depth-first
+    /// pre-order, along with their transformation relative to the root.
+    fn visit_nodes<'n, F>(&self, node: &Node<'n>, parent: &Matrix4, f: &mut F)
+    where
+        F: FnMut(&Node<'n>, &Matrix4),
+    {
+        // iterative, hostile files may nest deep enough to overflow the stack
+        let mut stack = vec![(node.clone(), *parent)];
+        while let Some((node, parent)) = stack.pop() {
+            let transform = prim::mat4_mul(&parent, &node.transform());
+            f(&node, &transform);
+            let first = stack.len();
+            stack.extend(node.children_iter().map(|child| (child, transform)));
+            stack[first..].reverse();
+        }
+    }
+}
+
+/// Walkable triangles of a mesh instance, see `Scene::walkable_triangles()`.
+#[derive(Debug, Clone)]
+pub struct WalkableSurface {
+    /// The name of the node instancing the mesh.
+    pub node: String,
+    /// Index into `Scene::meshes()`.
+    pub mesh: MeshHandle,
+    /// World space triangles, counter-clockwise seen from above.
+    pub triangles: Vec<[Vector3; 3]>,
+}
+
+/// An overview of a scene's contents, see `Scene::peek()`.
+#[derive(Debug, Clone)]
+pub struct SceneSummary {
+    pub flags: SceneFlags,
+    pub node_count: usize,
+    /// The number of vertices of all meshes.
+    pub vertex_count: usize,
+    /// The number of faces of all meshes.
+    pub face_count: usize,
+    pub mesh_names: Vec<String>,
+    pub material_names: Vec<String>,
+    /// Names and durations in seconds of all animations.
+    pub animations: Vec<(String, f64)>,
+    /// The number of embedded textures.
+    pub texture_count: usize,
+    pub light_count: usize,
+    pub camera_count: usize,
+}
+
+/// Memory used by a scene in bytes per category, see `Scene::memory_info()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryInfo {
+    /// Embedded textures.
+    pub textures: usize,
+    pub materials: usize,
+    /// Meshes, including their vertex data and bones.
+    pub meshes: usize,
+    pub nodes: usize,
+    /// Animations, including all channels and keys.
+    pub animations: usize,
+    pub cameras: usize,
+    pub lights: usize,
+    /// The sum of all categories plus the `aiScene` itself.
+    pub total: usize,
+}
+
+/// The extent of a scene, see `Scene::dimensions()`.
+#[derive(Debug, Clone, Copy)]
+pub struct Dimensions {
+    /// The world space bounding box in scene units.
+    pub aabb: Aabb,
+    /// The world space bounding box in meters.
+    pub aabb_meters: Aabb,
+    /// The conversion factor from scene units to meters.
+    pub meters_per_unit: f32,
+}
+
+/// A node name used by several nodes, see `Scene::name_collisions()`.
+#[derive(Debug, Clone)]
+pub struct NameCollision {
+    pub name: String,
+    /// The number of nodes with this name.
+    pub count: usize,
+    /// Whether a bone refers to this name.
+    pub bones: bool,
+    /// Whether an animation channel refers to this name.
+    pub channels: bool,
+}
+
+/// A metadata entry found by `Scene::query_metadata()`.
+pub struct MetadataMatch<'a> {
+    /// The node the entry belongs to.
+    pub node: Node<'a>,
+    pub key: &'a str,
+    pub value: MetadataValue<'a>,
+}
+
+/// A node animation channel together with its animation.
+#[derive(Clone, Copy)]
+pub struct NodeChannel<'a> {
+    pub animation: &'a Animation<'a>,
+    pub channel: &'a NodeAnim<'a>,
+}
+
+/// The animation channels of a scene by node, see
+/// `Scene::channels_by_node()`.
+#[derive(Clone)]
+pub struct NodeChannels<'a> {
+    /// Channels by the path of the node they animate.
+    pub by_path: HashMap<String, Vec<NodeChannel<'a>>>,
+    /// Channels referring to a node which doesn't exist in the hierarchy.
+    pub missing: Vec<NodeChannel<'a>>,
+}
+
+/// A texture and the material slots referencing it, see
+/// `Scene::texture_usage()`.
+#[derive(Debug, Clone)]
+pub struct TextureUsage {
+    pub texture: TextureRef,
+    pub uses: Vec<TextureUse>,
+}
+
+impl TextureUsage {
+    pub fn is_unused(&self) -> bool {
+        self.uses.is_empty()
+    }
+}
+
+/// A single material slot referencing a texture.
+#[derive(Debug, Clone, Copy)]
+pub struct TextureUse {
+    /// Index into `Scene::materials()`.
+    pub material: MaterialHandle,
+    pub tex_ty: TextureType,
+    /// Index into the material's texture stack for `tex_ty`.
+    pub idx: TextureIdx,
+}
+
+/// Meshes which were split from a single mesh by the importer.
+///
+/// See `Scene::original_mesh_groups()`.
+#[derive(Debug, Clone)]
+pub struct MeshGroup<'a> {
+    /// The name shared by all meshes of the group.
+    pub name: Option<&'a str>,
+    /// Indices into `Scene::meshes()`.
+    pub meshes: Vec<MeshHandle>,
 }
@@ -0,0 +1,376 @@
+use anim::Transform;
+use mesh::Mesh;
+use metadata::MetadataValue;
+use scene::{Node, Scene};
+use prim::{self, Aabb, Matrix4, Quaternion};
+use std::collections::HashMap;
+
+/// A single joint of a `Skeleton`.
+#[derive(Debug, Clone)]
+pub struct Joint {
+    /// The name of the node (and bone) this joint corresponds to.
+    pub name: String,
+    /// Index of the parent joint, `None` for root joints.
+    pub parent: Option<usize>,
+    /// The transformation relative to the parent node, in bind pose.
+    pub transform: Matrix4,
+    /// Matrix that transforms from mesh space to bone space in bind pose.
+    ///
+    /// Taken from the first mesh with a bone of this name. Joints which
+    /// are only ancestors of bones have the identity matrix here.
+    pub offset_matrix: Matrix4,
+}
+
+/// The joint hierarchy driving the skinned meshes of a scene.
+///
+/// Assimp stores bones per mesh and refers to the node hierarchy by name.
+/// A skeleton collects all nodes referenced by bones (plus their ancestors,
+/// so the hierarchy stays connected) into a flat list in which parents
+/// always precede their children.
+#[derive(Debug, Clone)]
+pub struct Skeleton {
+    joints: Vec<Joint>,
+    by_name: HashMap<String, usize>,
+}
+
+impl Skeleton {
+    /// Builds the skeleton from the bones of all meshes in the scene.
+    pub fn from_scene(scene: &Scene) -> Skeleton {
+        let mut offsets = HashMap::new();
+        for mesh in scene.meshes() {
+            for bone in mesh.bones() {
+                offsets.entry(bone.name().to_owned()).or_insert_with(|| bone.offset_matrix());
+            }
+        }
+
+        let mut skeleton = Skeleton { joints: Vec::new(), by_name: HashMap::new() };
+        if let Some(root) = scene.root_node() {
+            skeleton.collect(&root, &offsets);
+        }
+        skeleton
+    }
+
+    /// Adds the bones below `root` and all nodes between them and `root`,
+    /// parents before children.
+    fn collect(&mut self, root: &Node, offsets: &HashMap<String, Matrix4>) {
+        // iterative, hostile files may nest deep enough to overflow the stack
+        let mut nodes: Vec<(Node, Option<usize>)> = Vec::new();
+        let mut stack = vec![(root.clone(), None)];
+        while let Some((node, parent)) = stack.pop() {
+            let idx = nodes.len();
+            let first = stack.len();
+            stack.extend(node.children_iter().map(|child| (child, Some(idx))));
+            stack[first..].reverse();
+            nodes.push((node, parent));
+        }
+
+        // a node is kept if it or any descendant is a bone, children come
+        // after their parents in pre-order, so walk back to front
+        let mut used: Vec<bool> = nodes.iter().map(|&(ref node, _)| offsets.contains_key(node.name().unwrap_or(""))).collect();
+        for idx in (0..nodes.len()).rev() {
+            if let (true, Some(parent)) = (used[idx], nodes[idx].1) {
+                used[parent] = true;
+            }
+        }
+
+        let mut joint_idx: Vec<Option<usize>> = vec![None; nodes.len()];
+        for (idx, &(ref node, parent)) in nodes.iter().enumerate() {
+            if !used[idx] {
+                continue;
+            }
+            let name = node.name().unwrap_or("");
+            joint_idx[idx] = Some(self.joints.len());
+            self.by_name.entry(name.to_owned()).or_insert(self.joints.len());
+            self.joints.push(Joint {
+                name: name.to_owned(),
+                parent: parent.and_then(|parent| joint_idx[parent]),
+                transform: node.transform(),
+                offset_matrix: offsets.get(name).cloned().unwrap_or(prim::IDENTITY),
+            });
+        }
+    }
+
+    /// All joints, parents before children.
+    pub fn joints(&self) -> &[Joint] {
+        &self.joints
+    }
+
+    /// Index of the joint with the given name.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.by_name.get(name).cloned()
+    }
+
+    /// The local transformation of every joint in bind pose.
+    pub fn bind_pose(&self) -> Vec<Matrix4> {
+        self.joints.iter().map(|j| j.transform).collect()
+    }
+
+    /// Converts a pose of local joint transformations (one per joint, e.g.
+    /// `bind_pose()` or sampled from an animation) to transformations
+    /// relative to the skeleton root.
+    ///
+    /// # Panics
+    ///
+    /// If `pose` doesn't have one entry per joint.
+    pub fn global_transforms(&self, pose: &[Matrix4]) -> Vec<Matrix4> {
+        assert_eq!(pose.len(), self.joints.len());
+        let mut ret: Vec<Matrix4> = Vec::with_capacity(pose.len());
+        for (joint, local) in self.joints.iter().zip(pose) {
+            let global = match joint.parent {
+                Some(parent) => prim::mat4_mul(&ret[parent], local),
+                None => *local,
+            };
+            ret.push(global);
+        }
+        ret
+    }
+
+    /// The skinning matrices for a pose, ready to be uploaded to the GPU.
+    ///
+    /// `pose` contains the local transformation of every joint (see
+    /// `global_transforms()`). Each entry of the palette is the global joint
+    /// transformation multiplied with the joint's offset matrix, i.e. it maps
+    /// bind pose mesh space to posed space. Floats are written in native
+    /// byte order, one palette entry per joint.
+    ///
+    /// # Panics
+    ///
+    /// If `pose` doesn't have one entry per joint.
+    pub fn palette(&self, pose: &[Matrix4], layout: PaletteLayout) -> Vec<u8> {
+        let mut ret = Vec::with_capacity(self.joints.len() * layout.stride());
+        {
+            let mut push = |f: f32| ret.extend_from_slice(&f.to_bits().to_ne_bytes());
+            for (joint, global) in self.joints.iter().zip(self.global_transforms(pose)) {
+                let m = prim::mat4_mul(&global, &joint.offset_matrix);
+                match layout {
+                    PaletteLayout::Matrix4x4 => {
+                        for c in 0..4 {
+                            for r in 0..4 {
+                                push(m[r][c]);
+                            }
+                        }
+                    }
+                    PaletteLayout::Matrix4x3 => {
+                        for row in &m[..3] {
+                            for &v in row {
+                                push(v);
+                            }
+                        }
+                    }
+                    PaletteLayout::DualQuat => {
+                        // the rotation of a matrix with scaling isn't a unit
+                        // quaternion, decompose to strip the scaling first
+                        let [w, x, y, z]: Quaternion = Transform::from_matrix(&m).rotation;
+                        let [tx, ty, tz] = [m[0][3], m[1][3], m[2][3]];
+                        // dual part: 0.5 * (0, t) * q
+                        let dual = [
+                            -0.5 * (tx * x + ty * y + tz * z),
+                            0.5 * (tx * w + ty * z - tz * y),
+                            0.5 * (-tx * z + ty * w + tz * x),
+                            0.5 * (tx * y - ty * x + tz * w),
+                        ];
+                        for &v in &[x, y, z, w, dual[1], dual[2], dual[3], dual[0]] {
+                            push(v);
+                        }
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    /// Maps the bones of `mesh` to joints of this skeleton.
+    ///
+    /// Entry `i` is the joint index of `mesh.bones()[i]`, `None` if the
+    /// skeleton has no joint of that name (i.e. it was built from another
+    /// scene).
+    pub fn remap_table(&self, mesh: &Mesh) -> Vec<Option<usize>> {
+        mesh.bones().iter().map(|bone| self.find(bone.name())).collect()
+    }
+
+    /// The bounding box of the vertices influenced by each joint, in the
+    /// joint's bone space.
+    ///
+    /// Indexed by joint, `None` for joints which don't influence any vertex
+    /// of `mesh`. Transforming these boxes by the animated joint matrices
+    /// yields tight culling volumes for the skinned mesh.
+    pub fn per_bone_aabbs(&self, mesh: &Mesh) -> Vec<Option<Aabb>> {
+        let mut ret = vec![None; self.joints.len()];
+        let vertices = mesh.vertices();
+        for bone in mesh.bones() {
+            let joint = match self.find(bone.name()) {
+                Some(joint) => joint,
+                None => continue,
+            };
+            let offset = bone.offset_matrix();
+            let points = bone
+                .weights()
+                .iter()
+                .filter(|w| w.weight() > 0.0)
+                .filter_map(|w| vertices.get(w.vertex_idx() as usize))
+                .map(|&v| prim::transform_point(&offset, v));
+            if let Some(aabb) = Aabb::from_points(points) {
+                let merged: Option<Aabb> = ret[joint];
+                ret[joint] = Some(merged.map_or(aabb, |a| a.union(&aabb)));
+            }
+        }
+        ret
+    }
+}
+
+/// Memory layout of a single skinning matrix, see `Skeleton::palette()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaletteLayout {
+    /// 16 floats, column-major (e.g. a GLSL `mat4`).
+    Matrix4x4,
+    /// 12 floats, the first three rows of the matrix (e.g. `vec4[3]`).
+    Matrix4x3,
+    /// 8 floats, a unit dual quaternion: the rotation `x, y, z, w` followed
+    /// by the dual part `x, y, z, w`. Scaling is not preserved.
+    DualQuat,
+}
+
+impl PaletteLayout {
+    /// Size of one palette entry in bytes.
+    pub fn stride(&self) -> usize {
+        match *self {
+            PaletteLayout::Matrix4x4 => 64,
+            PaletteLayout::Matrix4x3 => 48,
+            PaletteLayout::DualQuat => 32,
+        }
+    }
+}
+
+// ++++++++++++++++++++ IK hints ++++++++++++++++++++
+
+/// An IK chain recovered from the conventions of the source file, see
+/// `Skeleton::ik_chain_hints()`.
+///
+/// Assimp doesn't import constraints, so these are hints for rebuilding
+/// the rig rather than an exact description of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IkChainHint {
+    /// The joint the chain ends in, e.g. a hand or foot.
+    pub effector: String,
+    /// The joints of the chain from its root down to and including the
+    /// effector.
+    pub chain: Vec<String>,
+    /// The node the chain's bend direction points towards, if any.
+    pub pole: Option<String>,
+}
+
+/// Metadata key naming the effector of an IK handle node (string).
+const IK_EFFECTOR_KEY: &'static str = "ik_effector";
+/// Metadata key on an IK handle node holding the number of joints above
+/// the effector (integer).
+const IK_CHAIN_LENGTH_KEY: &'static str = "ik_chain_length";
+/// Metadata key on an IK handle node naming its pole target (string).
+const IK_POLE_KEY: &'static str = "ik_pole";
+
+/// Name prefixes and suffixes (lowercase) of IK handle nodes, the rest of
+/// the name is the name of the effector.
+const IK_HANDLE_PREFIXES: &'static [&'static str] = &["ikhandle_", "ik_"];
+const IK_HANDLE_SUFFIXES: &'static [&'static str] = &["_ikhandle", "_ik_target", "_iktarget", "_ik"];
+/// Name suffixes and prefixes (lowercase) which, combined with the name of
+/// the effector, name the pole target.
+const IK_POLE_PREFIXES: &'static [&'static str] = &["pole_", "pv_"];
+const IK_POLE_SUFFIXES: &'static [&'static str] = &["_pole", "_pv"];
+
+/// Joints above the effector if the file doesn't say otherwise, i.e. a
+/// two-bone chain like upper arm, forearm and hand.
+const DEFAULT_IK_CHAIN_LENGTH: usize = 2;
+
+impl Skeleton {
+    /// Looks for IK handles in the node hierarchy of `scene`.
+    ///
+    /// A node is considered an IK handle if its metadata names an effector
+    /// under `"ik_effector"`, or if its name is the name of a joint with an
+    /// IK prefix or suffix (`IK_hand_l`, `hand_l_IK`, `hand_l_ikHandle`
+    /// and so on). The chain length is read from `"ik_chain_length"` and
+    /// defaults to two joints above the effector. The pole target is read
+    /// from `"ik_pole"` or found by name (`hand_l_pole`, `PV_hand_l`).
+    pub fn ik_chain_hints(&self, scene: &Scene) -> Vec<IkChainHint> {
+        let nodes: Vec<Node> = scene.root_node().iter().flat_map(|root| root.descendants()).collect();
+        let names: Vec<&str> = nodes.iter().filter_map(|node| node.name()).collect();
+
+        let mut ret: Vec<IkChainHint> = Vec::new();
+        for node in &nodes {
+            let name = node.name().unwrap_or("");
+            let meta = node.meta_data();
+            let meta_str = |key: &str| match meta.as_ref().and_then(|m| m.get(key)) {
+                Some(MetadataValue::String(s)) => Some(s),
+                _ => None,
+            };
+            let effector = match meta_str(IK_EFFECTOR_KEY).or_else(|| strip_affixes(name, IK_HANDLE_PREFIXES, IK_HANDLE_SUFFIXES)) {
+                Some(effector) => effector,
+                None => continue,
+            };
+            let joint = match self.find(effector) {
+                Some(joint) if effector != name => joint,
+                _ => continue,
+            };
+            if ret.iter().any(|hint| hint.effector == effector) {
+                continue;
+            }
+
+            let length = match meta.as_ref().and_then(|m| m.get(IK_CHAIN_LENGTH_KEY)) {
+                Some(MetadataValue::I32(n)) if n > 0 => n as usize,
+                Some(MetadataValue::U64(n)) if n > 0 => n as usize,
+                _ => DEFAULT_IK_CHAIN_LENGTH,
+            };
+            let mut chain = vec![self.joints[joint].name.clone()];
+            let mut parent = self.joints[joint].parent;
+            while let Some(idx) = parent {
+                if chain.len() > length {
+                    break;
+                }
+                chain.push(self.joints[idx].name.clone());
+                parent = self.joints[idx].parent;
+            }
+            chain.reverse();
+
+            let pole = meta_str(IK_POLE_KEY)
+                .or_else(|| {
+                    names.iter().cloned().find(|&candidate| {
+                        strip_affixes(candidate, IK_POLE_PREFIXES, IK_POLE_SUFFIXES) == Some(effector)
+                    })
+                })
+                .map(|pole| pole.to_owned());
+            ret.push(IkChainHint { effector: effector.to_owned(), chain, pole });
+        }
+        ret
+    }
+}
+
+/// `name` without the first matching prefix or suffix (compared
+/// case-insensitively), `None` if none matches or nothing would be left.
+fn strip_affixes<'a>(name: &'a str, prefixes: &[&str], suffixes: &[&str]) -> Option<&'a str> {
+    let lower = name.to_ascii_lowercase();
+    let stripped = prefixes
+        .iter()
+        .find(|p| lower.starts_with(*p))
+        .map(|p| &name[p.len()..])
+        .or_else(|| suffixes.iter().find(|s| lower.ends_with(*s)).map(|s| &name[..name.len() - s.len()]));
+    stripped.filter(|s| !s.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_affixes_prefix_and_suffix() {
+        assert_eq!(strip_affixes("IK_Hand_L", IK_HANDLE_PREFIXES, IK_HANDLE_SUFFIXES), Some("Hand_L"));
+        assert_eq!(strip_affixes("Foot_R_IKHandle", IK_HANDLE_PREFIXES, IK_HANDLE_SUFFIXES), Some("Foot_R"));
+        assert_eq!(strip_affixes("pv_Knee", IK_POLE_PREFIXES, IK_POLE_SUFFIXES), Some("Knee"));
+        assert_eq!(strip_affixes("Elbow_Pole", IK_POLE_PREFIXES, IK_POLE_SUFFIXES), Some("Elbow"));
+    }
+
+    #[test]
+    fn strip_affixes_no_match() {
+        assert_eq!(strip_affixes("Hand_L", IK_HANDLE_PREFIXES, IK_HANDLE_SUFFIXES), None);
+        // nothing left
+        assert_eq!(strip_affixes("_ik", IK_HANDLE_PREFIXES, IK_HANDLE_SUFFIXES), None);
+        assert_eq!(strip_affixes("pole_", IK_POLE_PREFIXES, IK_POLE_SUFFIXES), None);
+    }
+}